@@ -39,8 +39,8 @@ impl Auth {
             .map_err(Error)
     }
 
-    pub(crate) fn into_header_value(self) -> HeaderValue {
-        self.0
+    pub(crate) fn header_value(&self) -> &HeaderValue {
+        &self.0
     }
 }
 
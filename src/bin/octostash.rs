@@ -0,0 +1,430 @@
+use clap::{Parser, Subcommand};
+use futures_util::StreamExt;
+use octostash::{
+    stash::{Error, ImportReport, Listed, RateLimit, SyncAction, SyncReport, Visibility},
+    Auth, Stash,
+};
+use serde_json::{json, Value};
+use std::{
+    env, fs,
+    io::{self, Write},
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    process::ExitCode,
+    time::{Duration, UNIX_EPOCH},
+};
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+const TOKEN_VARS: [&str; 2] = ["OCTOSTASH_TOKEN", "GITHUB_TOKEN"];
+const CONFIG_VAR: &str = "OCTOSTASH_CONFIG";
+const SERVE_TOKEN_VAR: &str = "OCTOSTASH_SERVE_TOKEN";
+
+#[derive(Parser)]
+#[command(name = "octostash", version, about = "Store values in GitHub gists")]
+struct Cli {
+    #[arg(
+        long,
+        global = true,
+        help = "Print results as JSON, one object per line"
+    )]
+    json: bool,
+    #[arg(
+        long,
+        global = true,
+        value_name = "PATH",
+        help = "Config file holding `token = ...`"
+    )]
+    config: Option<PathBuf>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    #[command(about = "Insert a value from a file or stdin and print its id")]
+    Insert {
+        file: Option<PathBuf>,
+        #[arg(long, help = "Create a public gist instead of a secret one")]
+        public: bool,
+        #[arg(long, value_name = "SECONDS", conflicts_with = "public")]
+        ttl: Option<u64>,
+    },
+    #[command(about = "Print the value stored under an id")]
+    Get { id: String },
+    #[command(about = "Replace the value stored under an id from a file or stdin")]
+    Set { id: String, file: Option<PathBuf> },
+    #[command(about = "Remove one or more ids")]
+    Rm {
+        #[arg(required = true)]
+        ids: Vec<String>,
+    },
+    #[command(about = "List ids with their metadata")]
+    Ls {
+        #[arg(long, value_name = "USERNAME", help = "List another user's public ids")]
+        user: Option<String>,
+    },
+    #[command(about = "Write every entry as JSON Lines to a file or stdout")]
+    Export { file: Option<PathBuf> },
+    #[command(about = "Read JSON Lines from a file or stdin and print old to new ids")]
+    Import { file: Option<PathBuf> },
+    #[command(about = "Show the remaining GitHub API quota")]
+    RateLimit,
+    #[command(about = "Mirror a directory into a namespace of named keys and back")]
+    Sync {
+        dir: PathBuf,
+        #[arg(long, value_name = "ID", help = "Namespace id to sync with")]
+        namespace: Option<String>,
+        #[arg(long, help = "Show what would change without changing anything")]
+        dry_run: bool,
+    },
+    #[command(about = "Serve the stash over HTTP at /kv and /kv/{id}")]
+    Serve {
+        #[arg(long, value_name = "ADDR", default_value = "127.0.0.1:8080")]
+        addr: SocketAddr,
+        #[arg(
+            long,
+            value_name = "TOKEN",
+            help = "Require `Authorization: Bearer TOKEN` (defaults to $OCTOSTASH_SERVE_TOKEN)"
+        )]
+        auth_token: Option<String>,
+    },
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let json = cli.json;
+    let result = match token(cli.config.as_deref()) {
+        Ok(token) => match Auth::new(&token) {
+            Ok(auth) => run(Stash::new(auth), cli).await,
+            Err(err) => Err(err.to_string()),
+        },
+        Err(err) => Err(err),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            if json {
+                eprintln!("{}", json!({ "error": err }));
+            } else {
+                eprintln!("octostash: {err}");
+            }
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run(stash: Stash, cli: Cli) -> Result<(), String> {
+    let json = cli.json;
+    match cli.command {
+        Command::Insert { file, public, ttl } => {
+            let value = read_value(file.as_deref()).await?;
+            let id = match (ttl, public) {
+                (Some(ttl), _) => {
+                    stash
+                        .insert_with_ttl(&value, Duration::from_secs(ttl))
+                        .await
+                }
+                (None, true) => {
+                    stash
+                        .insert_with_visibility(&value, Visibility::Public)
+                        .await
+                }
+                (None, false) => stash.insert(&value).await,
+            }
+            .map_err(describe)?;
+            print(json, json!({ "id": id }), &id)
+        }
+        Command::Get { id } => {
+            let value = stash.get(&id).await.map_err(|err| describe_id(&id, err))?;
+            if json {
+                print(json, json!({ "id": id, "value": value }), "")
+            } else {
+                io::stdout()
+                    .lock()
+                    .write_all(value.as_bytes())
+                    .map_err(|err| err.to_string())
+            }
+        }
+        Command::Set { id, file } => {
+            let value = read_value(file.as_deref()).await?;
+            stash
+                .set(&id, &value)
+                .await
+                .map_err(|err| describe_id(&id, err))?;
+            print(json, json!({ "id": id }), &id)
+        }
+        Command::Rm { ids } => {
+            for id in ids {
+                stash
+                    .remove(&id)
+                    .await
+                    .map_err(|err| describe_id(&id, err))?;
+                print(json, json!({ "id": id }), &id)?;
+            }
+            Ok(())
+        }
+        Command::Ls { user } => {
+            let mut listing = match &user {
+                Some(username) => stash.user_listing(username),
+                None => stash.listing(),
+            };
+            while let Some(listed) = listing.next().await {
+                print_listed(json, &listed.map_err(describe)?)?;
+            }
+            Ok(())
+        }
+        Command::Export { file } => {
+            let exported = match &file {
+                Some(path) => {
+                    let file = tokio::fs::File::create(path)
+                        .await
+                        .map_err(|err| describe_path(path, err))?;
+                    stash.export(file).await
+                }
+                None => stash.export(tokio::io::stdout()).await,
+            }
+            .map_err(describe)?;
+            let summary = if json {
+                json!({ "exported": exported }).to_string()
+            } else {
+                format!("exported {exported} entries")
+            };
+            if file.is_some() {
+                println!("{summary}");
+            } else {
+                eprintln!("{summary}");
+            }
+            Ok(())
+        }
+        Command::Import { file } => {
+            let report = match &file {
+                Some(path) => {
+                    let file = tokio::fs::File::open(path)
+                        .await
+                        .map_err(|err| describe_path(path, err))?;
+                    stash.import(file).await
+                }
+                None => stash.import(tokio::io::stdin()).await,
+            };
+            print_import_report(json, &report)
+        }
+        Command::RateLimit => {
+            let rate_limit = stash.rate_limit().await.map_err(describe)?;
+            print_rate_limit(json, &rate_limit)
+        }
+        Command::Sync {
+            dir,
+            namespace,
+            dry_run,
+        } => {
+            let report = if dry_run {
+                stash.sync_dry_run(&dir, namespace.as_deref()).await
+            } else {
+                stash.sync(&dir, namespace.as_deref()).await
+            }
+            .map_err(|err| format!("{}: {err}", dir.display()))?;
+            print_sync_report(json, &report)
+        }
+        Command::Serve { addr, auth_token } => {
+            let auth_token = auth_token.or_else(|| {
+                env::var(SERVE_TOKEN_VAR)
+                    .ok()
+                    .map(|token| token.trim().to_owned())
+                    .filter(|token| !token.is_empty())
+            });
+            let shutdown = async {
+                tokio::signal::ctrl_c().await.ok();
+            };
+            octostash::server::serve_with_shutdown(stash, addr, auth_token.as_deref(), shutdown)
+                .await
+                .map_err(|err| format!("{addr}: {err}"))
+        }
+    }
+}
+
+fn token(config: Option<&Path>) -> Result<String, String> {
+    for var in TOKEN_VARS {
+        if let Ok(token) = env::var(var) {
+            if !token.trim().is_empty() {
+                return Ok(token.trim().to_owned());
+            }
+        }
+    }
+    let path = match config {
+        Some(path) => path.to_owned(),
+        None => config_path()
+            .filter(|path| path.exists())
+            .ok_or_else(|| format!("no token: set {} or create a config file", TOKEN_VARS[0]))?,
+    };
+    let config = fs::read_to_string(&path).map_err(|err| describe_path(&path, err))?;
+    config
+        .lines()
+        .filter_map(|line| line.trim().split_once('='))
+        .find(|(key, _)| key.trim() == "token")
+        .map(|(_, value)| value.trim().trim_matches('"').to_owned())
+        .filter(|token| !token.is_empty())
+        .ok_or_else(|| format!("no `token` in {}", path.display()))
+}
+
+fn config_path() -> Option<PathBuf> {
+    if let Some(path) = env::var_os(CONFIG_VAR) {
+        return Some(path.into());
+    }
+    let dir = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".config")))?;
+    Some(dir.join("octostash").join("config"))
+}
+
+async fn read_value(file: Option<&Path>) -> Result<String, String> {
+    match file {
+        Some(path) => tokio::fs::read_to_string(path)
+            .await
+            .map_err(|err| describe_path(path, err)),
+        None => read_to_string(tokio::io::stdin())
+            .await
+            .map_err(|err| err.to_string()),
+    }
+}
+
+async fn read_to_string<R>(mut reader: R) -> io::Result<String>
+where
+    R: AsyncRead + Unpin,
+{
+    let mut value = String::new();
+    reader.read_to_string(&mut value).await?;
+    Ok(value)
+}
+
+fn print(json: bool, value: Value, plain: &str) -> Result<(), String> {
+    let mut stdout = io::stdout().lock();
+    if json {
+        writeln!(stdout, "{value}")
+    } else {
+        writeln!(stdout, "{plain}")
+    }
+    .map_err(|err| err.to_string())
+}
+
+fn print_listed(json: bool, listed: &Listed) -> Result<(), String> {
+    let visibility = match listed.visibility() {
+        Visibility::Public => "public",
+        Visibility::Secret => "secret",
+    };
+    print(
+        json,
+        json!({
+            "id": listed.id(),
+            "visibility": visibility,
+            "created_at": listed.created_at(),
+            "updated_at": listed.updated_at(),
+            "html_url": listed.html_url(),
+        }),
+        &format!(
+            "{}\t{visibility}\t{}\t{}\t{}",
+            listed.id(),
+            listed.created_at(),
+            listed.updated_at(),
+            listed.html_url(),
+        ),
+    )
+}
+
+fn print_rate_limit(json: bool, rate_limit: &RateLimit) -> Result<(), String> {
+    let reset = rate_limit
+        .reset()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    print(
+        json,
+        json!({
+            "limit": rate_limit.limit(),
+            "remaining": rate_limit.remaining(),
+            "used": rate_limit.used(),
+            "reset": reset,
+        }),
+        &format!(
+            "limit\t{}\nremaining\t{}\nused\t{}\nreset\t{reset}",
+            rate_limit.limit(),
+            rate_limit.remaining(),
+            rate_limit.used(),
+        ),
+    )
+}
+
+fn print_sync_report(json: bool, report: &SyncReport) -> Result<(), String> {
+    for change in report.changes() {
+        let action = match change.action() {
+            SyncAction::Upload => "upload",
+            SyncAction::Download => "download",
+            SyncAction::DeleteRemote => "delete-remote",
+            SyncAction::DeleteLocal => "delete-local",
+            SyncAction::Conflict => "conflict",
+        };
+        print(
+            json,
+            json!({ "key": change.key(), "action": action }),
+            &format!("{action}\t{}", change.key()),
+        )?;
+    }
+    for (key, err) in report.failed() {
+        print(
+            json,
+            json!({ "key": key, "error": err.to_string() }),
+            &format!("failed\t{key}\t{err}"),
+        )?;
+    }
+    if let Some(namespace) = report.namespace() {
+        print(
+            json,
+            json!({ "namespace": namespace, "dry_run": report.is_dry_run() }),
+            &format!("namespace\t{namespace}"),
+        )?;
+    }
+    let conflicts = report.count(SyncAction::Conflict);
+    if conflicts > 0 || !report.failed().is_empty() {
+        return Err(format!(
+            "{conflicts} conflicts and {} failures left unsynced",
+            report.failed().len()
+        ));
+    }
+    Ok(())
+}
+
+fn print_import_report(json: bool, report: &ImportReport) -> Result<(), String> {
+    for (old, new) in report.imported() {
+        print(
+            json,
+            json!({ "old": old, "new": new }),
+            &format!("{old}\t{new}"),
+        )?;
+    }
+    for (line, err) in report.failed() {
+        print(
+            json,
+            json!({ "line": line, "error": err.to_string() }),
+            &format!("failed\tline {line}\t{err}"),
+        )?;
+    }
+    if !report.failed().is_empty() {
+        return Err(format!(
+            "{} records failed to import",
+            report.failed().len()
+        ));
+    }
+    Ok(())
+}
+
+fn describe(err: Error) -> String {
+    err.to_string()
+}
+
+fn describe_id(id: &str, err: Error) -> String {
+    format!("{id}: {err}")
+}
+
+fn describe_path(path: &Path, err: io::Error) -> String {
+    format!("{}: {err}", path.display())
+}
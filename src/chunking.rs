@@ -0,0 +1,65 @@
+use crate::format::{FILE_MAX_BYTES, FILE_MAX_CHARS};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Limit {
+    Bytes(usize),
+    Chars(usize),
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub enum Boundary {
+    #[default]
+    Any,
+    Line,
+    ContentDefined,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct Chunking {
+    limit: Limit,
+    boundary: Boundary,
+}
+
+impl Default for Chunking {
+    fn default() -> Self {
+        Self::chars(FILE_MAX_CHARS)
+    }
+}
+
+impl Chunking {
+    pub fn bytes(max: usize) -> Self {
+        Self::new(Limit::Bytes(max.clamp(1, FILE_MAX_BYTES)))
+    }
+
+    pub fn chars(max: usize) -> Self {
+        Self::new(Limit::Chars(max.clamp(1, FILE_MAX_CHARS)))
+    }
+
+    fn new(limit: Limit) -> Self {
+        Self {
+            limit,
+            boundary: Boundary::Any,
+        }
+    }
+
+    pub fn lines(self) -> Self {
+        self.with_boundary(Boundary::Line)
+    }
+
+    pub fn content_defined(self) -> Self {
+        self.with_boundary(Boundary::ContentDefined)
+    }
+
+    pub fn with_boundary(mut self, boundary: Boundary) -> Self {
+        self.boundary = boundary;
+        self
+    }
+
+    pub fn limit(&self) -> Limit {
+        self.limit
+    }
+
+    pub fn boundary(&self) -> Boundary {
+        self.boundary
+    }
+}
@@ -1,7 +1,33 @@
+mod bundle;
+mod cow_str;
+mod description;
 mod files;
 mod files_len;
+mod files_meta;
+mod gist_files;
 mod id;
+mod listing;
+mod manifest;
+mod metadata;
+mod rate_limit;
+mod record;
+mod revision;
+mod revisions;
+mod sync_state;
+mod trashed;
 
+pub use bundle::Bundle;
 pub use files::Files;
 pub use files_len::FilesLen;
+pub use files_meta::{FileMeta, FilesMeta};
+pub use gist_files::{GistFile, GistFiles};
 pub use id::{Id, IdArray};
+pub use listing::{ListedGist, Listing, Page};
+pub use manifest::{Manifest, LEGACY_VERSION};
+pub use metadata::Metadata;
+pub use rate_limit::RateLimit;
+pub use record::{Record, RecordContent};
+pub use revision::Revision;
+pub use revisions::Revisions;
+pub use sync_state::{SyncEntry, SyncState};
+pub use trashed::Trashed;
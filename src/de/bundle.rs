@@ -0,0 +1,140 @@
+use super::{
+    cow_str::CowStr, description::Description, files::FileContent, manifest::ManifestFile, Manifest,
+};
+use crate::format;
+use serde::{
+    de::{self, Error as _, MapAccess},
+    Deserialize, Deserializer,
+};
+use std::{borrow::Cow, collections::BTreeMap};
+
+struct CollectBundleContent;
+
+impl<'de> de::Visitor<'de> for CollectBundleContent {
+    type Value = BundleContent<'de>;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(formatter, "a map")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut output = BundleContent {
+            chunks: Vec::with_capacity(map.size_hint().unwrap_or_default()),
+            foreign: Vec::new(),
+            manifest: None,
+        };
+        while let Some(CowStr(key)) = map.next_key()? {
+            if key == format::MANIFEST_FILENAME {
+                let ManifestFile(manifest) = map.next_value()?;
+                output.manifest = Some(manifest);
+            } else if let Some((name, index)) = format::bundle_chunk(&key) {
+                let FileContent(content) = map.next_value()?;
+                output.chunks.push((name.to_owned(), index, content));
+            } else {
+                map.next_value::<de::IgnoredAny>()?;
+                output.foreign.push(key.into_owned());
+            }
+        }
+        Ok(output)
+    }
+}
+
+struct BundleContent<'de> {
+    chunks: Vec<(String, usize, Cow<'de, str>)>,
+    foreign: Vec<String>,
+    manifest: Option<Manifest>,
+}
+
+impl<'de> Deserialize<'de> for BundleContent<'de> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(CollectBundleContent)
+    }
+}
+
+struct FindBundle;
+
+impl<'de> de::Visitor<'de> for FindBundle {
+    type Value = Bundle<'de>;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(formatter, "a map with `files` key")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut bundle_content = None;
+        let mut trashed_at = None;
+        while let Some(CowStr(key)) = map.next_key()? {
+            match key.as_ref() {
+                "files" => bundle_content = Some(map.next_value()?),
+                "description" => {
+                    trashed_at = map.next_value::<Description>()?.trashed_at();
+                }
+                _ => {
+                    map.next_value::<de::IgnoredAny>()?;
+                }
+            }
+        }
+        Ok(Bundle(
+            bundle_content.ok_or_else(|| A::Error::custom("no `files`"))?,
+            trashed_at,
+        ))
+    }
+}
+
+pub struct Bundle<'de>(BundleContent<'de>, Option<u64>);
+
+impl<'de> Bundle<'de> {
+    pub fn trashed_at(&self) -> Option<u64> {
+        self.1
+    }
+
+    pub fn foreign(&self, bundled: &[String]) -> Option<&str> {
+        self.0.foreign.first().map(String::as_str).or_else(|| {
+            self.0
+                .chunks
+                .iter()
+                .find(|(name, _, _)| !bundled.contains(name))
+                .map(|(name, _, _)| name.as_str())
+        })
+    }
+
+    pub fn chunks_len(&self) -> usize {
+        self.0.chunks.len()
+    }
+
+    pub fn take_manifest(&mut self) -> Option<Manifest> {
+        self.0.manifest.take()
+    }
+}
+
+impl<'de> Deserialize<'de> for Bundle<'de> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(FindBundle)
+    }
+}
+
+impl<'de> From<Bundle<'de>> for BTreeMap<String, String> {
+    fn from(x: Bundle<'de>) -> Self {
+        let mut chunks = x.0.chunks;
+        chunks.sort_unstable_by(|(a_name, a_index, _), (b_name, b_index, _)| {
+            a_name.cmp(b_name).then(a_index.cmp(b_index))
+        });
+        let mut output = BTreeMap::<String, String>::new();
+        for (name, _, chunk) in chunks {
+            output.entry(name).or_default().push_str(&chunk);
+        }
+        output
+    }
+}
@@ -0,0 +1,26 @@
+use super::cow_str::CowStr;
+use crate::format;
+use serde::{Deserialize, Deserializer};
+use std::borrow::Cow;
+
+pub struct Description<'de>(pub Option<Cow<'de, str>>);
+
+impl Description<'_> {
+    pub fn trashed_at(&self) -> Option<u64> {
+        self.0.as_deref().and_then(format::trashed_at)
+    }
+
+    pub fn is_listed(&self) -> bool {
+        format::is_listed(self.0.as_deref())
+    }
+}
+
+impl<'de> Deserialize<'de> for Description<'de> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Option::<CowStr>::deserialize(deserializer)
+            .map(|description| Self(description.map(|CowStr(description)| description)))
+    }
+}
@@ -1,7 +1,10 @@
+use super::{cow_str::CowStr, description::Description, manifest::ManifestFile, Manifest};
+use crate::format;
 use serde::{
-    de::{self, Error as _, IgnoredAny, MapAccess},
+    de::{self, Error as _, MapAccess},
     Deserialize, Deserializer,
 };
+use std::{borrow::Cow, collections::HashMap};
 
 struct FindFileContent;
 
@@ -16,9 +19,9 @@ impl<'de> de::Visitor<'de> for FindFileContent {
     where
         A: MapAccess<'de>,
     {
-        while let Some(key) = map.next_key::<&str>()? {
-            if key == "content" {
-                let value = map.next_value()?;
+        while let Some(key) = map.next_key::<CowStr>()? {
+            if key.0 == "content" {
+                let CowStr(value) = map.next_value()?;
                 while map
                     .next_entry::<de::IgnoredAny, de::IgnoredAny>()?
                     .is_some()
@@ -32,7 +35,7 @@ impl<'de> de::Visitor<'de> for FindFileContent {
     }
 }
 
-struct FileContent<'de>(&'de str);
+pub(super) struct FileContent<'de>(pub(super) Cow<'de, str>);
 
 impl<'de> Deserialize<'de> for FileContent<'de> {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
@@ -46,7 +49,7 @@ impl<'de> Deserialize<'de> for FileContent<'de> {
 struct CollectFilesContent;
 
 impl<'de> de::Visitor<'de> for CollectFilesContent {
-    type Value = FilesContent;
+    type Value = FilesContent<'de>;
 
     fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
         write!(formatter, "a map")
@@ -56,17 +59,34 @@ impl<'de> de::Visitor<'de> for CollectFilesContent {
     where
         A: MapAccess<'de>,
     {
-        let mut output = String::new();
-        while let Some((_, FileContent(content))) = map.next_entry::<IgnoredAny, FileContent>()? {
-            output.push_str(content);
+        let mut output = FilesContent {
+            chunks: Vec::with_capacity(map.size_hint().unwrap_or_default()),
+            foreign: Vec::new(),
+            manifest: None,
+        };
+        while let Some(CowStr(key)) = map.next_key()? {
+            if key == format::MANIFEST_FILENAME {
+                let ManifestFile(manifest) = map.next_value()?;
+                output.manifest = Some(manifest);
+            } else if format::is_chunk_file(&key) {
+                let FileContent(content) = map.next_value()?;
+                output.chunks.push((key, content));
+            } else {
+                let FileContent(content) = map.next_value()?;
+                output.foreign.push((key, content));
+            }
         }
-        Ok(FilesContent(output))
+        Ok(output)
     }
 }
 
-struct FilesContent(String);
+struct FilesContent<'de> {
+    chunks: Vec<(Cow<'de, str>, Cow<'de, str>)>,
+    foreign: Vec<(Cow<'de, str>, Cow<'de, str>)>,
+    manifest: Option<Manifest>,
+}
 
-impl<'de> Deserialize<'de> for FilesContent {
+impl<'de> Deserialize<'de> for FilesContent<'de> {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: Deserializer<'de>,
@@ -78,7 +98,7 @@ impl<'de> Deserialize<'de> for FilesContent {
 struct FindFiles;
 
 impl<'de> de::Visitor<'de> for FindFiles {
-    type Value = Files;
+    type Value = Files<'de>;
 
     fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
         write!(formatter, "a map with `files` key")
@@ -88,29 +108,104 @@ impl<'de> de::Visitor<'de> for FindFiles {
     where
         A: MapAccess<'de>,
     {
-        while let Some(key) = map.next_key::<&str>()? {
-            if key == "files" {
-                let value = map.next_value()?;
-                while map
-                    .next_entry::<de::IgnoredAny, de::IgnoredAny>()?
-                    .is_some()
-                {}
-                return Ok(Files {
-                    files_content: value,
-                });
-            } else {
-                map.next_value::<de::IgnoredAny>()?;
+        let mut files_content = None;
+        let mut trashed_at = None;
+        while let Some(CowStr(key)) = map.next_key()? {
+            match key.as_ref() {
+                "files" => files_content = Some(map.next_value()?),
+                "description" => {
+                    trashed_at = map.next_value::<Description>()?.trashed_at();
+                }
+                _ => {
+                    map.next_value::<de::IgnoredAny>()?;
+                }
             }
         }
-        Err(A::Error::custom("no `files`"))
+        Ok(Files {
+            files_content: files_content.ok_or_else(|| A::Error::custom("no `files`"))?,
+            trashed_at,
+        })
     }
 }
 
-pub struct Files {
-    files_content: FilesContent,
+pub struct Files<'de> {
+    files_content: FilesContent<'de>,
+    trashed_at: Option<u64>,
 }
 
-impl<'de> Deserialize<'de> for Files {
+impl<'de> Files<'de> {
+    pub fn trashed_at(&self) -> Option<u64> {
+        self.trashed_at
+    }
+
+    pub fn children(&self) -> &[String] {
+        self.files_content
+            .manifest
+            .as_ref()
+            .map(|manifest| manifest.children.as_slice())
+            .unwrap_or_default()
+    }
+
+    pub fn take_manifest(&mut self) -> Option<Manifest> {
+        self.files_content.manifest.take()
+    }
+
+    pub fn foreign(&self) -> Option<&str> {
+        self.files_content
+            .foreign
+            .first()
+            .map(|(filename, _)| filename.as_ref())
+    }
+
+    pub fn into_value(self, manifest: Option<&Manifest>) -> Result<(String, usize), &'static str> {
+        let FilesContent {
+            mut chunks,
+            foreign,
+            ..
+        } = self.files_content;
+        let ordered = match manifest.and_then(Manifest::names) {
+            Some(names) => {
+                let contents = chunks
+                    .iter()
+                    .map(|(name, content)| (name.as_ref(), content.as_ref()))
+                    .collect::<HashMap<_, _>>();
+                names
+                    .iter()
+                    .map(|name| contents.get(name.as_str()).copied())
+                    .collect::<Option<Vec<_>>>()
+                    .ok_or("chunk is missing")?
+            }
+            None => {
+                chunks.retain(|(name, _)| format::chunk_index(name).is_some());
+                chunks.sort_unstable_by_key(|(name, _)| format::chunk_index(name));
+                chunks.iter().map(|(_, content)| content.as_ref()).collect()
+            }
+        };
+        let mut output = String::with_capacity(
+            ordered.iter().map(|chunk| chunk.len()).sum::<usize>()
+                + foreign
+                    .iter()
+                    .map(|(_, content)| content.len())
+                    .sum::<usize>(),
+        );
+        for chunk in &ordered {
+            output.push_str(chunk);
+        }
+        for (_, content) in &foreign {
+            output.push_str(content);
+        }
+        Ok((output, ordered.len()))
+    }
+
+    pub fn append_child(&mut self, child: Files<'de>) {
+        self.files_content.chunks.extend(child.files_content.chunks);
+        self.files_content
+            .foreign
+            .extend(child.files_content.foreign);
+    }
+}
+
+impl<'de> Deserialize<'de> for Files<'de> {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: Deserializer<'de>,
@@ -118,9 +213,3 @@ impl<'de> Deserialize<'de> for Files {
         deserializer.deserialize_map(FindFiles)
     }
 }
-
-impl From<Files> for String {
-    fn from(x: Files) -> Self {
-        x.files_content.0
-    }
-}
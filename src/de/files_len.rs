@@ -1,7 +1,10 @@
+use super::{cow_str::CowStr, description::Description, manifest::ManifestFile, Manifest};
+use crate::format;
 use serde::{
     de::{self, Error as _, IgnoredAny, MapAccess},
     Deserialize, Deserializer,
 };
+use std::borrow::Cow;
 
 struct FindFilenames;
 
@@ -16,20 +19,34 @@ impl<'de> de::Visitor<'de> for FindFilenames {
     where
         A: MapAccess<'de>,
     {
-        let mut output: Option<usize> = None;
-        while let Some((key, _)) = map.next_entry::<&str, IgnoredAny>()? {
-            if let Some(filename) = key.strip_prefix('_').and_then(|s| s.parse::<usize>().ok()) {
-                if !output.map(|current| filename < current).unwrap_or_default() {
-                    output = Some(filename);
-                }
+        let mut chunks = Vec::new();
+        let mut manifest = None;
+        let mut others = Vec::new();
+        while let Some(CowStr(key)) = map.next_key()? {
+            if key == format::MANIFEST_FILENAME {
+                let ManifestFile(value) = map.next_value()?;
+                manifest = Some(value);
+                continue;
+            }
+            map.next_value::<IgnoredAny>()?;
+            if format::is_chunk_file(&key) {
+                chunks.push(key.into_owned());
+            } else {
+                others.push(key.into_owned());
             }
         }
-        Ok(Filenames { max_index: output })
+        Ok(Filenames {
+            chunks,
+            manifest,
+            others,
+        })
     }
 }
 
 struct Filenames {
-    max_index: Option<usize>,
+    chunks: Vec<String>,
+    manifest: Option<Manifest>,
+    others: Vec<String>,
 }
 
 impl<'de> Deserialize<'de> for Filenames {
@@ -54,23 +71,88 @@ impl<'de> de::Visitor<'de> for FindFilesLen {
     where
         A: MapAccess<'de>,
     {
-        while let Some(key) = map.next_key::<&str>()? {
-            if key == "files" {
-                let value = map.next_value()?;
-                while map
-                    .next_entry::<de::IgnoredAny, de::IgnoredAny>()?
-                    .is_some()
-                {}
-                return Ok(FilesLen(value));
-            } else {
-                map.next_value::<de::IgnoredAny>()?;
+        let mut files = None;
+        let mut public = false;
+        let mut updated_at = None;
+        let mut description = None;
+        while let Some(CowStr(key)) = map.next_key()? {
+            match key.as_ref() {
+                "files" => files = Some(map.next_value()?),
+                "public" => public = map.next_value()?,
+                "updated_at" => updated_at = Some(map.next_value()?),
+                "description" => {
+                    let Description(value) = map.next_value()?;
+                    description = value.map(Cow::into_owned);
+                }
+                _ => {
+                    map.next_value::<de::IgnoredAny>()?;
+                }
             }
         }
-        Err(A::Error::custom("no `files`"))
+        Ok(FilesLen {
+            filenames: files.ok_or_else(|| A::Error::custom("no `files`"))?,
+            public,
+            updated_at: updated_at.unwrap_or_default(),
+            description: description.unwrap_or_default(),
+        })
     }
 }
 
-pub struct FilesLen(Filenames);
+pub struct FilesLen {
+    filenames: Filenames,
+    public: bool,
+    updated_at: String,
+    description: String,
+}
+
+impl FilesLen {
+    pub fn chunks(&self) -> &[String] {
+        &self.filenames.chunks
+    }
+
+    pub fn files_len(&self) -> usize {
+        self.filenames.chunks.len() + self.filenames.others.len()
+    }
+
+    pub fn public(&self) -> bool {
+        self.public
+    }
+
+    pub fn updated_at(&self) -> &str {
+        &self.updated_at
+    }
+
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    pub fn trashed_at(&self) -> Option<u64> {
+        format::trashed_at(&self.description)
+    }
+
+    pub fn manifest(&self) -> Option<&Manifest> {
+        self.filenames.manifest.as_ref()
+    }
+
+    pub fn foreign(&self) -> Option<&str> {
+        let bundled = self
+            .manifest()
+            .map(|manifest| manifest.files.as_slice())
+            .unwrap_or_default();
+        self.filenames
+            .others
+            .iter()
+            .find(|filename| {
+                !format::bundle_chunk(filename)
+                    .is_some_and(|(name, _)| bundled.iter().any(|file| file == name))
+            })
+            .map(String::as_str)
+    }
+
+    pub fn into_parts(self) -> (Vec<String>, Option<Manifest>) {
+        (self.filenames.chunks, self.filenames.manifest)
+    }
+}
 
 impl<'de> Deserialize<'de> for FilesLen {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
@@ -80,9 +162,3 @@ impl<'de> Deserialize<'de> for FilesLen {
         deserializer.deserialize_map(FindFilesLen)
     }
 }
-
-impl From<FilesLen> for usize {
-    fn from(files: FilesLen) -> Self {
-        files.0.max_index.unwrap_or_default() + 1
-    }
-}
@@ -0,0 +1,222 @@
+use super::{cow_str::CowStr, description::Description, manifest::ManifestFile, Manifest};
+use crate::format;
+use serde::{
+    de::{self, Error as _, MapAccess},
+    Deserialize, Deserializer,
+};
+use std::{collections::HashMap, mem};
+
+struct FindFileMeta;
+
+impl<'de> de::Visitor<'de> for FindFileMeta {
+    type Value = FileMetaFields;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(formatter, "a map with `size` and `raw_url` keys")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut size = None;
+        let mut raw_url = None;
+        while let Some(CowStr(key)) = map.next_key()? {
+            match key.as_ref() {
+                "size" => size = Some(map.next_value()?),
+                "raw_url" => raw_url = Some(map.next_value()?),
+                _ => {
+                    map.next_value::<de::IgnoredAny>()?;
+                }
+            }
+        }
+        Ok(FileMetaFields {
+            size: size.ok_or_else(|| A::Error::custom("no `size`"))?,
+            raw_url: raw_url.ok_or_else(|| A::Error::custom("no `raw_url`"))?,
+        })
+    }
+}
+
+struct FileMetaFields {
+    size: usize,
+    raw_url: String,
+}
+
+impl<'de> Deserialize<'de> for FileMetaFields {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(FindFileMeta)
+    }
+}
+
+#[derive(Clone)]
+pub struct FileMeta {
+    pub index: usize,
+    pub name: String,
+    pub size: usize,
+    pub raw_url: String,
+}
+
+struct CollectFilesMeta;
+
+impl<'de> de::Visitor<'de> for CollectFilesMeta {
+    type Value = FilesMeta;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(formatter, "a map")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut output = FilesMeta {
+            files: Vec::with_capacity(map.size_hint().unwrap_or_default()),
+            others: Vec::new(),
+            manifest: None,
+            foreign: None,
+            trashed_at: None,
+        };
+        while let Some(CowStr(key)) = map.next_key()? {
+            if key == format::MANIFEST_FILENAME {
+                let ManifestFile(manifest) = map.next_value()?;
+                output.manifest = Some(manifest);
+            } else if format::is_chunk_file(&key) {
+                let FileMetaFields { size, raw_url } = map.next_value()?;
+                output.files.push(FileMeta {
+                    index: format::chunk_index(&key).unwrap_or(usize::MAX),
+                    name: key.into_owned(),
+                    size,
+                    raw_url,
+                });
+            } else {
+                let FileMetaFields { size, raw_url } = map.next_value()?;
+                if output.foreign.is_none() {
+                    output.foreign = Some(key.clone().into_owned());
+                }
+                output.others.push(FileMeta {
+                    index: usize::MAX,
+                    name: key.into_owned(),
+                    size,
+                    raw_url,
+                });
+            }
+        }
+        Ok(output)
+    }
+}
+
+pub(super) struct FilesMetaList(pub(super) FilesMeta);
+
+impl<'de> Deserialize<'de> for FilesMetaList {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(CollectFilesMeta).map(Self)
+    }
+}
+
+struct FindFilesMeta;
+
+impl<'de> de::Visitor<'de> for FindFilesMeta {
+    type Value = FilesMeta;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(formatter, "a map with `files` key")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut files_meta = None;
+        let mut trashed_at = None;
+        while let Some(CowStr(key)) = map.next_key()? {
+            match key.as_ref() {
+                "files" => {
+                    let FilesMetaList(value) = map.next_value()?;
+                    files_meta = Some(value);
+                }
+                "description" => {
+                    trashed_at = map.next_value::<Description>()?.trashed_at();
+                }
+                _ => {
+                    map.next_value::<de::IgnoredAny>()?;
+                }
+            }
+        }
+        let mut files_meta = files_meta.ok_or_else(|| A::Error::custom("no `files`"))?;
+        files_meta.trashed_at = trashed_at;
+        Ok(files_meta)
+    }
+}
+
+pub struct FilesMeta {
+    pub files: Vec<FileMeta>,
+    pub others: Vec<FileMeta>,
+    pub manifest: Option<Manifest>,
+    pub foreign: Option<String>,
+    pub trashed_at: Option<u64>,
+}
+
+impl FilesMeta {
+    pub fn append_child(&mut self, child: FilesMeta) {
+        self.files.extend(child.files);
+        self.others.extend(child.others);
+        self.foreign = self.foreign.take().or(child.foreign);
+    }
+
+    pub fn arrange(&mut self, manifest: Option<&Manifest>) {
+        if let Some(manifest) = manifest.filter(|manifest| !manifest.files.is_empty()) {
+            let mut files = mem::take(&mut self.others)
+                .into_iter()
+                .filter_map(|file| {
+                    let (name, index) = format::bundle_chunk(&file.name)?;
+                    let position = manifest.files.iter().position(|file| file == name)?;
+                    Some(((position, index), file))
+                })
+                .collect::<Vec<_>>();
+            files.sort_unstable_by_key(|(key, _)| *key);
+            self.files = files
+                .into_iter()
+                .enumerate()
+                .map(|(index, (_, file))| FileMeta { index, ..file })
+                .collect();
+            return;
+        }
+        match manifest.and_then(Manifest::names) {
+            Some(names) => {
+                let files = mem::take(&mut self.files)
+                    .into_iter()
+                    .map(|file| (file.name.clone(), file))
+                    .collect::<HashMap<_, _>>();
+                self.files = names
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(index, name)| {
+                        files.get(name).map(|file| FileMeta {
+                            index,
+                            ..file.clone()
+                        })
+                    })
+                    .collect();
+            }
+            None => {
+                self.files.retain(|file| file.index != usize::MAX);
+                self.files.sort_unstable_by_key(|file| file.index);
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for FilesMeta {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(FindFilesMeta)
+    }
+}
@@ -0,0 +1,143 @@
+use super::cow_str::CowStr;
+use serde::{
+    de::{self, Error as _, MapAccess},
+    Deserialize, Deserializer,
+};
+
+struct FindGistFile;
+
+impl<'de> de::Visitor<'de> for FindGistFile {
+    type Value = GistFileFields;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(formatter, "a map with `raw_url` key")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut content = None;
+        let mut truncated = false;
+        let mut raw_url = None;
+        while let Some(CowStr(key)) = map.next_key()? {
+            match key.as_ref() {
+                "content" => content = Some(map.next_value()?),
+                "truncated" => truncated = map.next_value()?,
+                "raw_url" => raw_url = Some(map.next_value()?),
+                _ => {
+                    map.next_value::<de::IgnoredAny>()?;
+                }
+            }
+        }
+        Ok(GistFileFields {
+            content: content.unwrap_or_default(),
+            truncated,
+            raw_url: raw_url.ok_or_else(|| A::Error::custom("no `raw_url`"))?,
+        })
+    }
+}
+
+struct GistFileFields {
+    content: String,
+    truncated: bool,
+    raw_url: String,
+}
+
+impl<'de> Deserialize<'de> for GistFileFields {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(FindGistFile)
+    }
+}
+
+pub struct GistFile {
+    pub filename: String,
+    pub content: String,
+    pub truncated: bool,
+    pub raw_url: String,
+}
+
+struct CollectGistFiles;
+
+impl<'de> de::Visitor<'de> for CollectGistFiles {
+    type Value = Vec<GistFile>;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(formatter, "a map")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut output = Vec::with_capacity(map.size_hint().unwrap_or_default());
+        while let Some(CowStr(key)) = map.next_key()? {
+            let GistFileFields {
+                content,
+                truncated,
+                raw_url,
+            } = map.next_value()?;
+            output.push(GistFile {
+                filename: key.into_owned(),
+                content,
+                truncated,
+                raw_url,
+            });
+        }
+        Ok(output)
+    }
+}
+
+struct GistFilesList(Vec<GistFile>);
+
+impl<'de> Deserialize<'de> for GistFilesList {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(CollectGistFiles).map(Self)
+    }
+}
+
+struct FindGistFiles;
+
+impl<'de> de::Visitor<'de> for FindGistFiles {
+    type Value = GistFiles;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(formatter, "a map with `files` key")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        while let Some(CowStr(key)) = map.next_key()? {
+            if key == "files" {
+                let GistFilesList(value) = map.next_value()?;
+                while map
+                    .next_entry::<de::IgnoredAny, de::IgnoredAny>()?
+                    .is_some()
+                {}
+                return Ok(GistFiles(value));
+            } else {
+                map.next_value::<de::IgnoredAny>()?;
+            }
+        }
+        Err(A::Error::custom("no `files`"))
+    }
+}
+
+pub struct GistFiles(pub Vec<GistFile>);
+
+impl<'de> Deserialize<'de> for GistFiles {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(FindGistFiles)
+    }
+}
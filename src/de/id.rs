@@ -1,5 +1,6 @@
 use std::array;
 
+use super::{cow_str::CowStr, description::Description};
 use serde::{
     de::{self, Error as _, SeqAccess},
     Deserialize, Deserializer,
@@ -68,6 +69,48 @@ impl From<Id> for String {
     }
 }
 
+struct FindListedId;
+
+impl<'de> de::Visitor<'de> for FindListedId {
+    type Value = ListedId;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("a map containing an `id` field")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::MapAccess<'de>,
+    {
+        let mut id = None;
+        let mut listed = true;
+        while let Some(CowStr(key)) = map.next_key()? {
+            match key.as_ref() {
+                "id" => id = Some(map.next_value::<CowStr>()?.0.into_owned()),
+                "description" => {
+                    listed = map.next_value::<Description>()?.is_listed();
+                }
+                _ => {
+                    map.next_value::<de::IgnoredAny>()?;
+                }
+            }
+        }
+        let id = id.ok_or_else(|| A::Error::custom("no `id`"))?;
+        Ok(ListedId(listed.then_some(id)))
+    }
+}
+
+struct ListedId(Option<String>);
+
+impl<'de> Deserialize<'de> for ListedId {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(FindListedId)
+    }
+}
+
 pub struct CollectIdArray<const N: usize>;
 
 impl<'de, const N: usize> de::Visitor<'de> for CollectIdArray<N> {
@@ -83,18 +126,33 @@ impl<'de, const N: usize> de::Visitor<'de> for CollectIdArray<N> {
     {
         let mut buf = array::from_fn(|_| String::new());
         let mut index = 0;
-        while let Some(Id(id)) = seq.next_element()? {
-            *buf.get_mut(index)
-                .ok_or_else(|| A::Error::custom("array length is insufficient"))? = id;
-            index += 1;
+        let mut listed_len = 0;
+        while let Some(ListedId(id)) = seq.next_element()? {
+            listed_len += 1;
+            if let Some(id) = id {
+                *buf.get_mut(index)
+                    .ok_or_else(|| A::Error::custom("array length is insufficient"))? = id;
+                index += 1;
+            }
         }
-        Ok(IdArray { buf, len: index })
+        Ok(IdArray {
+            buf,
+            len: index,
+            listed_len,
+        })
     }
 }
 
 pub struct IdArray<const N: usize> {
     buf: [String; N],
     len: usize,
+    listed_len: usize,
+}
+
+impl<const N: usize> IdArray<N> {
+    pub fn listed_len(&self) -> usize {
+        self.listed_len
+    }
 }
 
 impl<'de, const N: usize> Deserialize<'de> for IdArray<N> {
@@ -0,0 +1,116 @@
+use super::{cow_str::CowStr, description::Description};
+use serde::{
+    de::{self, Error as _, MapAccess, SeqAccess},
+    Deserialize, Deserializer,
+};
+
+struct FindListedGist;
+
+impl<'de> de::Visitor<'de> for FindListedGist {
+    type Value = MaybeListedGist;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(formatter, "a map with `id`, `public` and `html_url` keys")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut id = None;
+        let mut public = None;
+        let mut html_url = None;
+        let mut created_at = None;
+        let mut updated_at = None;
+        let mut listed = true;
+        while let Some(CowStr(key)) = map.next_key()? {
+            match key.as_ref() {
+                "id" => id = Some(map.next_value()?),
+                "public" => public = Some(map.next_value()?),
+                "html_url" => html_url = Some(map.next_value()?),
+                "created_at" => created_at = Some(map.next_value()?),
+                "updated_at" => updated_at = Some(map.next_value()?),
+                "description" => {
+                    listed = map.next_value::<Description>()?.is_listed();
+                }
+                _ => {
+                    map.next_value::<de::IgnoredAny>()?;
+                }
+            }
+        }
+        let gist = ListedGist {
+            id: id.ok_or_else(|| A::Error::custom("no `id`"))?,
+            public: public.ok_or_else(|| A::Error::custom("no `public`"))?,
+            html_url: html_url.ok_or_else(|| A::Error::custom("no `html_url`"))?,
+            created_at: created_at.unwrap_or_default(),
+            updated_at: updated_at.unwrap_or_default(),
+        };
+        Ok(MaybeListedGist(listed.then_some(gist)))
+    }
+}
+
+struct MaybeListedGist(Option<ListedGist>);
+
+impl<'de> Deserialize<'de> for MaybeListedGist {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(FindListedGist)
+    }
+}
+
+pub struct ListedGist {
+    pub id: String,
+    pub public: bool,
+    pub html_url: String,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+struct CollectListing;
+
+impl<'de> de::Visitor<'de> for CollectListing {
+    type Value = Listing;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(formatter, "an array")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut gists = Vec::with_capacity(seq.size_hint().unwrap_or_default());
+        let mut listed_len = 0;
+        while let Some(MaybeListedGist(gist)) = seq.next_element()? {
+            listed_len += 1;
+            gists.extend(gist);
+        }
+        Ok(Listing { gists, listed_len })
+    }
+}
+
+pub trait Page {
+    fn listed_len(&self) -> usize;
+}
+
+pub struct Listing {
+    pub gists: Vec<ListedGist>,
+    pub listed_len: usize,
+}
+
+impl Page for Listing {
+    fn listed_len(&self) -> usize {
+        self.listed_len
+    }
+}
+
+impl<'de> Deserialize<'de> for Listing {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(CollectListing)
+    }
+}
@@ -0,0 +1,187 @@
+use super::cow_str::CowStr;
+use crate::format;
+use serde::{
+    de::{self, Error as _, MapAccess},
+    Deserialize, Deserializer,
+};
+
+struct FindManifest;
+
+impl<'de> de::Visitor<'de> for FindManifest {
+    type Value = Manifest;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(formatter, "a map with `chunks` key")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut version = None;
+        let mut len = None;
+        let mut children = None;
+        let mut files = None;
+        let mut names = None;
+        let mut chunks = None;
+        let mut sha256 = None;
+        let mut chunk_sha256 = None;
+        let mut expires_at = None;
+        while let Some(CowStr(key)) = map.next_key()? {
+            match key.as_ref() {
+                "version" => version = Some(map.next_value()?),
+                "encoding" => {
+                    let CowStr(encoding) = map.next_value()?;
+                    if encoding != format::ENCODING {
+                        return Err(A::Error::custom(format_args!(
+                            "unsupported encoding `{encoding}`"
+                        )));
+                    }
+                }
+                "compression" => {
+                    let CowStr(compression) = map.next_value()?;
+                    if compression != format::COMPRESSION {
+                        return Err(A::Error::custom(format_args!(
+                            "unsupported compression `{compression}`"
+                        )));
+                    }
+                }
+                "len" => len = Some(map.next_value()?),
+                "children" => children = Some(map.next_value()?),
+                "files" => files = Some(map.next_value()?),
+                "names" => names = Some(map.next_value()?),
+                "chunks" => chunks = Some(map.next_value()?),
+                "sha256" => sha256 = Some(map.next_value()?),
+                "chunk_sha256" => chunk_sha256 = Some(map.next_value()?),
+                "expires_at" => expires_at = map.next_value()?,
+                _ => {
+                    map.next_value::<de::IgnoredAny>()?;
+                }
+            }
+        }
+        let version = version.unwrap_or(LEGACY_VERSION);
+        if version > format::FORMAT_VERSION {
+            return Err(A::Error::custom(format_args!(
+                "unsupported format version {version}"
+            )));
+        }
+        Ok(Manifest {
+            version,
+            len,
+            children: children.unwrap_or_default(),
+            files: files.unwrap_or_default(),
+            names,
+            chunks: chunks.ok_or_else(|| A::Error::custom("no `chunks`"))?,
+            sha256,
+            chunk_sha256: chunk_sha256.unwrap_or_default(),
+            expires_at,
+        })
+    }
+}
+
+pub const LEGACY_VERSION: u32 = 0;
+
+pub struct Manifest {
+    pub version: u32,
+    pub len: Option<usize>,
+    pub children: Vec<String>,
+    pub files: Vec<String>,
+    pub names: Option<Vec<String>>,
+    pub chunks: usize,
+    pub sha256: Option<String>,
+    pub chunk_sha256: Vec<String>,
+    pub expires_at: Option<u64>,
+}
+
+impl Manifest {
+    pub fn names(&self) -> Option<&[String]> {
+        self.names.as_deref()
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expires_at
+            .is_some_and(|expires_at| expires_at <= format::unix_time())
+    }
+
+    pub fn check_chunks(&self, chunks: usize) -> Result<(), &'static str> {
+        if self.chunks == chunks {
+            Ok(())
+        } else {
+            Err("chunk count mismatch")
+        }
+    }
+
+    pub fn check_len(&self, len: usize) -> Result<(), &'static str> {
+        match self.len {
+            Some(expected) if expected != len => Err("length mismatch"),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn check_sha256(&self, sha256: &str) -> Result<(), &'static str> {
+        match &self.sha256 {
+            Some(expected) if expected != sha256 => Err("sha256 mismatch"),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn check_chunk_sha256(&self, index: usize, chunk: &str) -> Result<(), &'static str> {
+        match self.chunk_sha256.get(index) {
+            Some(expected) if *expected != format::sha256(chunk.as_bytes()) => {
+                Err("chunk sha256 mismatch")
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Manifest {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(FindManifest)
+    }
+}
+
+struct FindManifestContent;
+
+impl<'de> de::Visitor<'de> for FindManifestContent {
+    type Value = ManifestFile;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(formatter, "a map with `content` key")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        while let Some(CowStr(key)) = map.next_key()? {
+            if key == "content" {
+                let CowStr(content) = map.next_value()?;
+                while map
+                    .next_entry::<de::IgnoredAny, de::IgnoredAny>()?
+                    .is_some()
+                {}
+                return serde_json::from_str(&content)
+                    .map(ManifestFile)
+                    .map_err(A::Error::custom);
+            } else {
+                map.next_value::<de::IgnoredAny>()?;
+            }
+        }
+        Err(A::Error::custom("no `content`"))
+    }
+}
+
+pub struct ManifestFile(pub Manifest);
+
+impl<'de> Deserialize<'de> for ManifestFile {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(FindManifestContent)
+    }
+}
@@ -0,0 +1,78 @@
+use super::{cow_str::CowStr, description::Description, files_meta::FilesMetaList, FilesMeta};
+use serde::{
+    de::{self, Error as _, MapAccess},
+    Deserialize, Deserializer,
+};
+
+struct FindMetadata;
+
+impl<'de> de::Visitor<'de> for FindMetadata {
+    type Value = Metadata;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            formatter,
+            "a map with `id`, `public`, `html_url` and `files` keys"
+        )
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut id = None;
+        let mut public = None;
+        let mut html_url = None;
+        let mut created_at = None;
+        let mut updated_at = None;
+        let mut files = None;
+        let mut trashed_at = None;
+        while let Some(CowStr(key)) = map.next_key()? {
+            match key.as_ref() {
+                "id" => id = Some(map.next_value()?),
+                "public" => public = Some(map.next_value()?),
+                "html_url" => html_url = Some(map.next_value()?),
+                "created_at" => created_at = Some(map.next_value()?),
+                "updated_at" => updated_at = Some(map.next_value()?),
+                "files" => {
+                    let FilesMetaList(value) = map.next_value()?;
+                    files = Some(value);
+                }
+                "description" => {
+                    trashed_at = map.next_value::<Description>()?.trashed_at();
+                }
+                _ => {
+                    map.next_value::<de::IgnoredAny>()?;
+                }
+            }
+        }
+        let mut files: FilesMeta = files.ok_or_else(|| A::Error::custom("no `files`"))?;
+        files.trashed_at = trashed_at;
+        Ok(Metadata {
+            id: id.ok_or_else(|| A::Error::custom("no `id`"))?,
+            public: public.ok_or_else(|| A::Error::custom("no `public`"))?,
+            html_url: html_url.ok_or_else(|| A::Error::custom("no `html_url`"))?,
+            created_at: created_at.unwrap_or_default(),
+            updated_at: updated_at.unwrap_or_default(),
+            files,
+        })
+    }
+}
+
+pub struct Metadata {
+    pub id: String,
+    pub public: bool,
+    pub html_url: String,
+    pub created_at: String,
+    pub updated_at: String,
+    pub files: FilesMeta,
+}
+
+impl<'de> Deserialize<'de> for Metadata {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(FindMetadata)
+    }
+}
@@ -0,0 +1,101 @@
+use super::cow_str::CowStr;
+use serde::{
+    de::{self, Error as _, MapAccess},
+    Deserialize, Deserializer,
+};
+
+struct FindRate;
+
+impl<'de> de::Visitor<'de> for FindRate {
+    type Value = Rate;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            formatter,
+            "a map with `limit`, `remaining`, `used` and `reset` keys"
+        )
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut limit = None;
+        let mut remaining = None;
+        let mut used = None;
+        let mut reset = None;
+        while let Some(CowStr(key)) = map.next_key()? {
+            match key.as_ref() {
+                "limit" => limit = Some(map.next_value()?),
+                "remaining" => remaining = Some(map.next_value()?),
+                "used" => used = Some(map.next_value()?),
+                "reset" => reset = Some(map.next_value()?),
+                _ => {
+                    map.next_value::<de::IgnoredAny>()?;
+                }
+            }
+        }
+        Ok(Rate(RateLimit {
+            limit: limit.ok_or_else(|| A::Error::custom("no `limit`"))?,
+            remaining: remaining.ok_or_else(|| A::Error::custom("no `remaining`"))?,
+            used: used.unwrap_or_default(),
+            reset: reset.ok_or_else(|| A::Error::custom("no `reset`"))?,
+        }))
+    }
+}
+
+struct Rate(RateLimit);
+
+impl<'de> Deserialize<'de> for Rate {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(FindRate)
+    }
+}
+
+struct FindRateLimit;
+
+impl<'de> de::Visitor<'de> for FindRateLimit {
+    type Value = RateLimit;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("a map containing a `rate` field")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut rate = None;
+        while let Some(CowStr(key)) = map.next_key()? {
+            match key.as_ref() {
+                "rate" => {
+                    let Rate(value) = map.next_value()?;
+                    rate = Some(value);
+                }
+                _ => {
+                    map.next_value::<de::IgnoredAny>()?;
+                }
+            }
+        }
+        rate.ok_or_else(|| A::Error::custom("no `rate`"))
+    }
+}
+
+pub struct RateLimit {
+    pub limit: u64,
+    pub remaining: u64,
+    pub used: u64,
+    pub reset: u64,
+}
+
+impl<'de> Deserialize<'de> for RateLimit {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(FindRateLimit)
+    }
+}
@@ -0,0 +1,65 @@
+use super::cow_str::CowStr;
+use serde::{
+    de::{self, Error as _, MapAccess},
+    Deserialize, Deserializer,
+};
+use std::collections::BTreeMap;
+
+struct FindRecord;
+
+impl<'de> de::Visitor<'de> for FindRecord {
+    type Value = Record;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(formatter, "a map with `id` and `value` or `files` keys")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut id = None;
+        let mut public = false;
+        let mut expires_at = None;
+        let mut content = None;
+        while let Some(CowStr(key)) = map.next_key()? {
+            match key.as_ref() {
+                "id" => id = Some(map.next_value()?),
+                "public" => public = map.next_value()?,
+                "expires_at" => expires_at = map.next_value()?,
+                "value" => content = Some(RecordContent::Value(map.next_value()?)),
+                "files" => content = Some(RecordContent::Files(map.next_value()?)),
+                _ => {
+                    map.next_value::<de::IgnoredAny>()?;
+                }
+            }
+        }
+        Ok(Record {
+            id: id.ok_or_else(|| A::Error::custom("no `id`"))?,
+            public,
+            expires_at,
+            content: content.ok_or_else(|| A::Error::custom("no `value` or `files`"))?,
+        })
+    }
+}
+
+pub enum RecordContent {
+    Value(String),
+    Files(BTreeMap<String, String>),
+}
+
+pub struct Record {
+    pub id: String,
+    pub public: bool,
+    pub expires_at: Option<u64>,
+    pub content: RecordContent,
+}
+
+impl<'de> Deserialize<'de> for Record {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(FindRecord)
+    }
+}
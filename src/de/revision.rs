@@ -0,0 +1,118 @@
+use super::cow_str::CowStr;
+use serde::{
+    de::{self, Error as _, MapAccess, SeqAccess},
+    Deserialize, Deserializer,
+};
+
+struct FindVersion;
+
+impl<'de> de::Visitor<'de> for FindVersion {
+    type Value = Option<String>;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(formatter, "a map with `version` key")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut version = None;
+        while let Some(CowStr(key)) = map.next_key()? {
+            if key == "version" {
+                version = Some(map.next_value()?);
+            } else {
+                map.next_value::<de::IgnoredAny>()?;
+            }
+        }
+        Ok(version)
+    }
+}
+
+struct Version(Option<String>);
+
+impl<'de> Deserialize<'de> for Version {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(FindVersion).map(Self)
+    }
+}
+
+struct FindLatestVersion;
+
+impl<'de> de::Visitor<'de> for FindLatestVersion {
+    type Value = Option<String>;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(formatter, "a sequence")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let latest = seq
+            .next_element::<Version>()?
+            .and_then(|Version(version)| version);
+        while seq.next_element::<de::IgnoredAny>()?.is_some() {}
+        Ok(latest)
+    }
+}
+
+struct LatestVersion(Option<String>);
+
+impl<'de> Deserialize<'de> for LatestVersion {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(FindLatestVersion).map(Self)
+    }
+}
+
+struct FindRevision;
+
+impl<'de> de::Visitor<'de> for FindRevision {
+    type Value = Revision;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(formatter, "a map with `history` or `updated_at` key")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut history = None;
+        let mut updated_at = None;
+        while let Some(CowStr(key)) = map.next_key()? {
+            match key.as_ref() {
+                "history" => {
+                    let LatestVersion(version) = map.next_value()?;
+                    history = version;
+                }
+                "updated_at" => updated_at = Some(map.next_value()?),
+                _ => {
+                    map.next_value::<de::IgnoredAny>()?;
+                }
+            }
+        }
+        history
+            .or(updated_at)
+            .map(Revision)
+            .ok_or_else(|| A::Error::custom("no `history` or `updated_at`"))
+    }
+}
+
+pub struct Revision(pub String);
+
+impl<'de> Deserialize<'de> for Revision {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(FindRevision)
+    }
+}
@@ -0,0 +1,96 @@
+use super::{cow_str::CowStr, description::Description, Page};
+use serde::{
+    de::{self, Error as _, MapAccess, SeqAccess},
+    Deserialize, Deserializer,
+};
+
+struct FindListedRevision;
+
+impl<'de> de::Visitor<'de> for FindListedRevision {
+    type Value = ListedRevision;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("a map containing `id` and `updated_at` fields")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut id = None;
+        let mut updated_at = None;
+        let mut listed = true;
+        while let Some(CowStr(key)) = map.next_key()? {
+            match key.as_ref() {
+                "id" => id = Some(map.next_value::<CowStr>()?.0.into_owned()),
+                "updated_at" => updated_at = Some(map.next_value::<CowStr>()?.0.into_owned()),
+                "description" => {
+                    listed = map.next_value::<Description>()?.is_listed();
+                }
+                _ => {
+                    map.next_value::<de::IgnoredAny>()?;
+                }
+            }
+        }
+        let id = id.ok_or_else(|| A::Error::custom("no `id`"))?;
+        let updated_at = updated_at.ok_or_else(|| A::Error::custom("no `updated_at`"))?;
+        Ok(ListedRevision(listed.then_some((id, updated_at))))
+    }
+}
+
+struct ListedRevision(Option<(String, String)>);
+
+impl<'de> Deserialize<'de> for ListedRevision {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(FindListedRevision)
+    }
+}
+
+struct CollectRevisions;
+
+impl<'de> de::Visitor<'de> for CollectRevisions {
+    type Value = Revisions;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(formatter, "an array")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut revisions = Vec::with_capacity(seq.size_hint().unwrap_or_default());
+        let mut listed_len = 0;
+        while let Some(ListedRevision(revision)) = seq.next_element()? {
+            listed_len += 1;
+            revisions.extend(revision);
+        }
+        Ok(Revisions {
+            revisions,
+            listed_len,
+        })
+    }
+}
+
+pub struct Revisions {
+    pub revisions: Vec<(String, String)>,
+    pub listed_len: usize,
+}
+
+impl Page for Revisions {
+    fn listed_len(&self) -> usize {
+        self.listed_len
+    }
+}
+
+impl<'de> Deserialize<'de> for Revisions {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(CollectRevisions)
+    }
+}
@@ -0,0 +1,101 @@
+use super::cow_str::CowStr;
+use serde::{
+    de::{self, Error as _, MapAccess},
+    Deserialize, Deserializer,
+};
+use std::collections::BTreeMap;
+
+struct FindSyncEntry;
+
+impl<'de> de::Visitor<'de> for FindSyncEntry {
+    type Value = SyncEntry;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(formatter, "a map with `id`, `sha256` and `revision` keys")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut id = None;
+        let mut sha256 = None;
+        let mut revision = None;
+        while let Some(CowStr(key)) = map.next_key()? {
+            match key.as_ref() {
+                "id" => id = Some(map.next_value()?),
+                "sha256" => sha256 = Some(map.next_value()?),
+                "revision" => revision = Some(map.next_value()?),
+                _ => {
+                    map.next_value::<de::IgnoredAny>()?;
+                }
+            }
+        }
+        Ok(SyncEntry {
+            id: id.ok_or_else(|| A::Error::custom("no `id`"))?,
+            sha256: sha256.ok_or_else(|| A::Error::custom("no `sha256`"))?,
+            revision: revision.ok_or_else(|| A::Error::custom("no `revision`"))?,
+        })
+    }
+}
+
+pub struct SyncEntry {
+    pub id: String,
+    pub sha256: String,
+    pub revision: String,
+}
+
+impl<'de> Deserialize<'de> for SyncEntry {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(FindSyncEntry)
+    }
+}
+
+struct FindSyncState;
+
+impl<'de> de::Visitor<'de> for FindSyncState {
+    type Value = SyncState;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(formatter, "a map with `namespace` and `entries` keys")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut namespace = None;
+        let mut entries = None;
+        while let Some(CowStr(key)) = map.next_key()? {
+            match key.as_ref() {
+                "namespace" => namespace = map.next_value()?,
+                "entries" => entries = Some(map.next_value()?),
+                _ => {
+                    map.next_value::<de::IgnoredAny>()?;
+                }
+            }
+        }
+        Ok(SyncState {
+            namespace,
+            entries: entries.unwrap_or_default(),
+        })
+    }
+}
+
+#[derive(Default)]
+pub struct SyncState {
+    pub namespace: Option<String>,
+    pub entries: BTreeMap<String, SyncEntry>,
+}
+
+impl<'de> Deserialize<'de> for SyncState {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(FindSyncState)
+    }
+}
@@ -0,0 +1,93 @@
+use super::{cow_str::CowStr, description::Description, Page};
+use serde::{
+    de::{self, Error as _, MapAccess, SeqAccess},
+    Deserialize, Deserializer,
+};
+
+struct FindListedTrashed;
+
+impl<'de> de::Visitor<'de> for FindListedTrashed {
+    type Value = ListedTrashed;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("a map containing an `id` field")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut id = None;
+        let mut trashed_at = None;
+        while let Some(CowStr(key)) = map.next_key()? {
+            match key.as_ref() {
+                "id" => id = Some(map.next_value::<CowStr>()?.0.into_owned()),
+                "description" => {
+                    trashed_at = map.next_value::<Description>()?.trashed_at();
+                }
+                _ => {
+                    map.next_value::<de::IgnoredAny>()?;
+                }
+            }
+        }
+        let id = id.ok_or_else(|| A::Error::custom("no `id`"))?;
+        Ok(ListedTrashed(trashed_at.map(|trashed_at| (id, trashed_at))))
+    }
+}
+
+struct ListedTrashed(Option<(String, u64)>);
+
+impl<'de> Deserialize<'de> for ListedTrashed {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(FindListedTrashed)
+    }
+}
+
+struct CollectTrashed;
+
+impl<'de> de::Visitor<'de> for CollectTrashed {
+    type Value = Trashed;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(formatter, "an array")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut trashed = Vec::new();
+        let mut listed_len = 0;
+        while let Some(ListedTrashed(entry)) = seq.next_element()? {
+            listed_len += 1;
+            trashed.extend(entry);
+        }
+        Ok(Trashed {
+            trashed,
+            listed_len,
+        })
+    }
+}
+
+pub struct Trashed {
+    pub trashed: Vec<(String, u64)>,
+    pub listed_len: usize,
+}
+
+impl Page for Trashed {
+    fn listed_len(&self) -> usize {
+        self.listed_len
+    }
+}
+
+impl<'de> Deserialize<'de> for Trashed {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(CollectTrashed)
+    }
+}
@@ -0,0 +1,115 @@
+pub const FILE_MAX_CHARS: usize = 250000;
+
+pub const FILE_MAX_BYTES: usize = 4 * FILE_MAX_CHARS;
+
+pub const GIST_MAX_CHUNKS: usize = 299;
+
+pub const MANIFEST_FILENAME: &str = "octostash.json";
+
+pub const FORMAT_VERSION: u32 = 2;
+
+pub const ENCODING: &str = "utf-8";
+
+pub const COMPRESSION: &str = "none";
+
+pub const CHILD_DESCRIPTION: &str = "octostash child";
+
+pub const TRASH_DESCRIPTION_PREFIX: &str = "octostash trash ";
+
+pub const CHUNK_SEPARATOR: char = '_';
+
+pub const CHUNK_NAME_LEN: usize = 32;
+
+pub fn hex(bytes: &[u8]) -> String {
+    const HEX: &[u8; 16] = b"0123456789abcdef";
+    let mut output = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        output.push(HEX[(byte >> 4) as usize] as char);
+        output.push(HEX[(byte & 0xf) as usize] as char);
+    }
+    output
+}
+
+pub fn sha256(data: &[u8]) -> String {
+    use sha2::Digest as _;
+    hex(&sha2::Sha256::digest(data))
+}
+
+pub fn trim_to_chars(bytes: &[u8]) -> &[u8] {
+    let start = bytes
+        .iter()
+        .position(|byte| byte & 0xc0 != 0x80)
+        .unwrap_or(bytes.len());
+    let bytes = &bytes[start..];
+    match std::str::from_utf8(bytes) {
+        Err(err) if err.error_len().is_none() => &bytes[..err.valid_up_to()],
+        _ => bytes,
+    }
+}
+
+pub fn unix_time() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+pub fn trash_description(trashed_at: u64, description: &str) -> String {
+    if description.is_empty() {
+        format!("{TRASH_DESCRIPTION_PREFIX}{trashed_at}")
+    } else {
+        format!("{TRASH_DESCRIPTION_PREFIX}{trashed_at} {description}")
+    }
+}
+
+fn split_trash_description(description: &str) -> Option<(u64, &str)> {
+    let marker = description.strip_prefix(TRASH_DESCRIPTION_PREFIX)?;
+    let (trashed_at, description) = marker.split_once(' ').unwrap_or((marker, ""));
+    Some((trashed_at.parse().ok()?, description))
+}
+
+pub fn trashed_at(description: &str) -> Option<u64> {
+    split_trash_description(description).map(|(trashed_at, _)| trashed_at)
+}
+
+pub fn restored_description(description: &str) -> &str {
+    split_trash_description(description).map_or(description, |(_, description)| description)
+}
+
+pub fn is_listed(description: Option<&str>) -> bool {
+    description.is_none_or(|description| {
+        description != CHILD_DESCRIPTION && trashed_at(description).is_none()
+    })
+}
+
+pub fn chunk_index(filename: &str) -> Option<usize> {
+    filename
+        .strip_prefix(CHUNK_SEPARATOR)
+        .filter(|s| s.len() < CHUNK_NAME_LEN)
+        .and_then(|s| s.parse().ok())
+}
+
+pub fn chunk_name(sha256: &str) -> String {
+    format!("{CHUNK_SEPARATOR}{}", &sha256[..CHUNK_NAME_LEN])
+}
+
+pub fn is_chunk_name(filename: &str) -> bool {
+    filename.strip_prefix(CHUNK_SEPARATOR).is_some_and(|hash| {
+        hash.len() == CHUNK_NAME_LEN
+            && hash
+                .bytes()
+                .all(|byte| byte.is_ascii_digit() || (b'a'..=b'f').contains(&byte))
+    })
+}
+
+pub fn is_chunk_file(filename: &str) -> bool {
+    is_chunk_name(filename) || chunk_index(filename).is_some()
+}
+
+pub fn bundle_chunk(filename: &str) -> Option<(&str, usize)> {
+    let (name, index) = filename.rsplit_once(CHUNK_SEPARATOR)?;
+    index.parse().ok().map(|index| (name, index))
+}
+
+pub fn bundle_filename(name: &str, index: usize) -> String {
+    format!("{name}{CHUNK_SEPARATOR}{index}")
+}
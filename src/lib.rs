@@ -1,8 +1,13 @@
 pub mod auth;
+pub mod chunking;
+#[cfg(feature = "server")]
+pub mod server;
 pub mod stash;
 
 mod de;
+mod format;
 mod ser;
 
 pub use auth::Auth;
-pub use stash::Stash;
+pub use chunking::Chunking;
+pub use stash::{ReadOnlyStash, Stash};
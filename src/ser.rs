@@ -1,3 +1,19 @@
+mod bundle;
+mod chunker;
+mod digest;
 mod files;
+mod files_encoder;
+mod manifest;
+mod record;
+mod removal;
+mod sync_state;
 
-pub use files::Files;
+pub use bundle::Bundle;
+pub use chunker::Chunker;
+pub use digest::Digest;
+pub use files::{chunks, Files, Layout};
+pub use files_encoder::FilesEncoder;
+pub use manifest::Manifest;
+pub use record::{Record, RecordContent};
+pub use removal::Removal;
+pub use sync_state::{SyncEntry, SyncState};
@@ -0,0 +1,64 @@
+use super::{files::FileContent, Manifest};
+use crate::format;
+use serde::{ser::SerializeMap as _, Serialize, Serializer};
+
+struct BundleContent<'a> {
+    files: &'a [(&'a str, Vec<&'a str>)],
+    manifest: &'a Manifest<'a>,
+}
+
+impl<'a> Serialize for BundleContent<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let manifest = unsafe { serde_json::to_string(self.manifest).unwrap_unchecked() };
+        let mut map = serializer.serialize_map(Some(
+            self.files
+                .iter()
+                .map(|(_, chunks)| chunks.len())
+                .sum::<usize>()
+                + 1,
+        ))?;
+        for (name, chunks) in self.files {
+            for (index, chunk) in chunks.iter().enumerate() {
+                map.serialize_entry(&format::bundle_filename(name, index), &FileContent(chunk))?;
+            }
+        }
+        map.serialize_entry(format::MANIFEST_FILENAME, &FileContent(&manifest))?;
+        map.end()
+    }
+}
+
+pub struct Bundle<'a> {
+    public: bool,
+    content: BundleContent<'a>,
+}
+
+impl<'a> Serialize for Bundle<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(1 + usize::from(self.public)))?;
+        if self.public {
+            map.serialize_entry("public", &true)?;
+        }
+        map.serialize_entry("files", &self.content)?;
+        map.end()
+    }
+}
+
+impl<'a> Bundle<'a> {
+    pub fn new(files: &'a [(&'a str, Vec<&'a str>)], manifest: &'a Manifest<'a>) -> Self {
+        Self {
+            public: false,
+            content: BundleContent { files, manifest },
+        }
+    }
+
+    pub fn with_public(mut self, public: bool) -> Self {
+        self.public = public;
+        self
+    }
+}
@@ -0,0 +1,110 @@
+use crate::chunking::{Boundary, Chunking, Limit};
+
+const GEAR: [u64; 256] = {
+    let mut table = [0; 256];
+    let mut state = 0x6f63_746f_7374_6173_u64;
+    let mut index = 0;
+    while index < table.len() {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        table[index] = z ^ (z >> 31);
+        index += 1;
+    }
+    table
+};
+
+pub struct Chunker {
+    max: usize,
+    is_bytes: bool,
+    boundary: Boundary,
+    min: usize,
+    mask: u64,
+    chars: usize,
+    bytes: usize,
+    hash: u64,
+}
+
+impl Chunker {
+    pub fn new(chunking: Chunking) -> Self {
+        let (max, is_bytes) = match chunking.limit() {
+            Limit::Bytes(max) => (max, true),
+            Limit::Chars(max) => (max, false),
+        };
+        let (min, mask) = match chunking.boundary() {
+            Boundary::Any => (max, 0),
+            Boundary::Line => (max / 2, 0),
+            Boundary::ContentDefined => {
+                let min = max / 4;
+                (min, !(u64::MAX >> min.max(1).ilog2()))
+            }
+        };
+        Self {
+            max,
+            is_bytes,
+            boundary: chunking.boundary(),
+            min,
+            mask,
+            chars: 0,
+            bytes: 0,
+            hash: 0,
+        }
+    }
+
+    fn size(&self) -> usize {
+        if self.is_bytes {
+            self.bytes
+        } else {
+            self.chars
+        }
+    }
+
+    fn exceeds(&self, ch: char) -> bool {
+        if self.is_bytes {
+            self.bytes + ch.len_utf8() > self.max
+        } else {
+            self.chars + 1 > self.max
+        }
+    }
+
+    fn push(&mut self, ch: char) {
+        self.chars += 1;
+        self.bytes += ch.len_utf8();
+        if self.boundary == Boundary::ContentDefined {
+            let mut buf = [0; 4];
+            for &byte in ch.encode_utf8(&mut buf).as_bytes() {
+                self.hash = (self.hash << 1).wrapping_add(GEAR[byte as usize]);
+            }
+        }
+    }
+
+    fn is_boundary(&self, ch: char) -> bool {
+        match self.boundary {
+            Boundary::Any => false,
+            Boundary::Line => ch == '\n' && self.size() >= self.min,
+            Boundary::ContentDefined => self.size() >= self.min && self.hash & self.mask == 0,
+        }
+    }
+
+    fn reset(&mut self) {
+        self.chars = 0;
+        self.bytes = 0;
+        self.hash = 0;
+    }
+
+    pub fn split(&mut self, input: &str) -> (usize, bool) {
+        for (index, ch) in input.char_indices() {
+            if self.bytes != 0 && self.exceeds(ch) {
+                self.reset();
+                return (index, true);
+            }
+            self.push(ch);
+            if self.is_boundary(ch) {
+                self.reset();
+                return (index + ch.len_utf8(), true);
+            }
+        }
+        (input.len(), false)
+    }
+}
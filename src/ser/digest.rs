@@ -0,0 +1,68 @@
+use crate::format;
+use sha2::{Digest as _, Sha256};
+
+pub struct Digest {
+    value: Sha256,
+    chunk: Sha256,
+    chunk_hashes: bool,
+    chunks: Vec<String>,
+    names: Vec<String>,
+    len: usize,
+}
+
+impl Digest {
+    pub fn new(chunk_hashes: bool) -> Self {
+        Self {
+            value: Sha256::new(),
+            chunk: Sha256::new(),
+            chunk_hashes,
+            chunks: Vec::new(),
+            names: Vec::new(),
+            len: 0,
+        }
+    }
+
+    pub fn from_chunks(chunks: &[&str], chunk_hashes: bool) -> Self {
+        let mut digest = Self::new(chunk_hashes);
+        for chunk in chunks {
+            digest.update(chunk);
+            digest.end_chunk();
+        }
+        digest
+    }
+
+    pub fn update(&mut self, piece: &str) {
+        self.value.update(piece);
+        self.len += piece.len();
+        self.chunk.update(piece);
+    }
+
+    pub fn end_chunk(&mut self) -> &str {
+        let sha256 = format::hex(&self.chunk.finalize_reset());
+        self.names.push(format::chunk_name(&sha256));
+        if self.chunk_hashes {
+            self.chunks.push(sha256);
+        }
+        unsafe { self.names.last().unwrap_unchecked() }
+    }
+
+    pub fn chunks_len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn sha256(&self) -> String {
+        format::hex(&self.value.clone().finalize())
+    }
+
+    pub fn chunk_sha256(&self) -> &[String] {
+        &self.chunks
+    }
+
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+}
@@ -1,6 +1,12 @@
+use super::{Chunker, Manifest};
+use crate::{
+    chunking::Chunking,
+    format::{self, GIST_MAX_CHUNKS},
+};
 use serde::{ser::SerializeMap as _, Serialize, Serializer};
+use std::collections::HashSet;
 
-struct FileContent<'a>(&'a str);
+pub(super) struct FileContent<'a>(pub(super) &'a str);
 
 impl<'a> Serialize for FileContent<'a> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
@@ -13,49 +19,60 @@ impl<'a> Serialize for FileContent<'a> {
     }
 }
 
-struct FilesContentChunks<'a>(&'a str);
-
-const FILE_MAX_CHARS: usize = 250000;
-
-impl<'a> Iterator for FilesContentChunks<'a> {
-    type Item = &'a str;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.0.is_empty() {
-            None
-        } else if let Some((file_end, _)) = self.0.char_indices().nth(FILE_MAX_CHARS) {
-            let (file, remainder) = self.0.split_at(file_end);
-            self.0 = remainder;
-            Some(file)
-        } else {
-            let file = self.0;
-            self.0 = "";
-            Some(file)
-        }
+pub fn chunks(mut value: &str, chunking: Chunking) -> Vec<&str> {
+    let mut chunker = Chunker::new(chunking);
+    let mut chunks = Vec::new();
+    while !value.is_empty() {
+        let (chunk_end, _) = chunker.split(value);
+        let (chunk, remainder) = value.split_at(chunk_end);
+        chunks.push(chunk);
+        value = remainder;
     }
+    chunks
 }
 
-struct FilenameBuffer([u8; 21]);
-
-impl FilenameBuffer {
-    fn new() -> Self {
-        let mut bytes = [0; 21];
-        bytes[0] = '_' as u8;
-        Self(bytes)
-    }
+pub struct Layout<'a> {
+    pub root: Vec<(&'a str, &'a str)>,
+    pub children: Vec<Vec<(&'a str, &'a str)>>,
+    pub removed: Vec<String>,
+}
 
-    fn fmt(&mut self, i: usize) -> &str {
-        let mut itoa_buf = itoa::Buffer::new();
-        let bytes = itoa_buf.format(i).as_bytes();
-        let end = bytes.len() + 1;
-        unsafe { self.0.get_unchecked_mut(1..end) }.copy_from_slice(bytes);
-        unsafe { std::str::from_utf8_unchecked(&self.0[..end]) }
+impl<'a> Layout<'a> {
+    pub fn new(chunks: &[&'a str], names: &'a [String], current: &[String]) -> Self {
+        let current = current.iter().map(String::as_str).collect::<HashSet<_>>();
+        let mut seen = HashSet::new();
+        let unique = names
+            .iter()
+            .map(String::as_str)
+            .zip(chunks.iter().copied())
+            .filter(|(name, _)| seen.insert(*name))
+            .collect::<Vec<_>>();
+        let kept = unique
+            .iter()
+            .filter(|(name, _)| current.contains(name))
+            .count();
+        let (root, rest) = unique
+            .into_iter()
+            .filter(|(name, _)| !current.contains(name))
+            .enumerate()
+            .partition::<Vec<_>, _>(|(index, _)| kept + index < GIST_MAX_CHUNKS);
+        let rest = rest.into_iter().map(|(_, chunk)| chunk).collect::<Vec<_>>();
+        Self {
+            root: root.into_iter().map(|(_, chunk)| chunk).collect(),
+            children: rest.chunks(GIST_MAX_CHUNKS).map(<[_]>::to_vec).collect(),
+            removed: current
+                .into_iter()
+                .filter(|name| !seen.contains(name))
+                .map(str::to_owned)
+                .collect(),
+        }
     }
 }
 
 struct FilesContent<'a> {
-    files_content: &'a str,
-    current_len: usize,
+    chunks: &'a [(&'a str, &'a str)],
+    removed: &'a [String],
+    manifest: Option<Manifest<'a>>,
 }
 
 impl<'a> Serialize for FilesContent<'a> {
@@ -63,37 +80,81 @@ impl<'a> Serialize for FilesContent<'a> {
     where
         S: Serializer,
     {
-        let new_count = FilesContentChunks(self.files_content).count();
-        let mut map = serializer.serialize_map(Some(new_count))?;
-        let mut filename_buf = FilenameBuffer::new();
-        for (index, file_content) in FilesContentChunks(self.files_content).enumerate() {
-            map.serialize_entry(filename_buf.fmt(index), &FileContent(file_content))?;
+        let manifest = self
+            .manifest
+            .as_ref()
+            .map(|manifest| unsafe { serde_json::to_string(manifest).unwrap_unchecked() });
+        let mut map = serializer.serialize_map(Some(
+            self.chunks.len() + self.removed.len() + usize::from(manifest.is_some()),
+        ))?;
+        for (name, file_content) in self.chunks {
+            map.serialize_entry(name, &FileContent(file_content))?;
+        }
+        for name in self.removed {
+            map.serialize_entry(name, &())?;
         }
-        for index in new_count..self.current_len {
-            map.serialize_entry(filename_buf.fmt(index), &())?;
+        if let Some(manifest) = manifest {
+            map.serialize_entry(format::MANIFEST_FILENAME, &FileContent(&manifest))?;
         }
         map.end()
     }
 }
 
-pub struct Files<'a>(FilesContent<'a>);
+pub struct Files<'a> {
+    description: Option<&'a str>,
+    public: bool,
+    content: FilesContent<'a>,
+}
 
 impl<'a> Serialize for Files<'a> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
-        let mut map = serializer.serialize_map(Some(1))?;
-        map.serialize_entry("files", &self.0)?;
+        let mut map = serializer.serialize_map(Some(
+            1 + usize::from(self.description.is_some()) + usize::from(self.public),
+        ))?;
+        if let Some(description) = self.description {
+            map.serialize_entry("description", description)?;
+        }
+        if self.public {
+            map.serialize_entry("public", &true)?;
+        }
+        map.serialize_entry("files", &self.content)?;
         map.end()
     }
 }
 
 impl<'a> Files<'a> {
-    pub fn new(files_content: &'a str, current_len: usize) -> Self {
-        Self(FilesContent {
-            files_content,
-            current_len,
-        })
+    pub fn new(chunks: &'a [(&'a str, &'a str)]) -> Self {
+        Self {
+            description: None,
+            public: false,
+            content: FilesContent {
+                chunks,
+                removed: &[],
+                manifest: None,
+            },
+        }
+    }
+
+    pub fn with_removed(mut self, removed: &'a [String]) -> Self {
+        self.content.removed = removed;
+        self
+    }
+
+    pub fn with_description(mut self, description: &'a str) -> Self {
+        self.description = Some(description);
+        self
+    }
+
+    pub fn with_public(mut self, public: bool) -> Self {
+        self.public = public;
+        self
+    }
+
+    pub fn with_manifest(mut self, manifest: Manifest<'a>) -> Self {
+        self.content.manifest = Some(manifest);
+        self
     }
 }
@@ -0,0 +1,223 @@
+use super::{Chunker, Digest};
+use crate::{chunking::Chunking, format};
+use std::{
+    collections::HashSet,
+    str::{self, Utf8Error},
+};
+
+pub struct FilesEncoder {
+    description: Option<&'static str>,
+    public: bool,
+    incomplete: Vec<u8>,
+    chunk: String,
+    max_files: usize,
+    written: Vec<String>,
+    chunker: Chunker,
+    opened: bool,
+}
+
+impl FilesEncoder {
+    pub fn new(max_files: usize, chunking: Chunking) -> Self {
+        Self {
+            description: None,
+            public: false,
+            incomplete: Vec::new(),
+            chunk: String::new(),
+            max_files,
+            written: Vec::new(),
+            chunker: Chunker::new(chunking),
+            opened: false,
+        }
+    }
+
+    pub fn with_description(mut self, description: &'static str) -> Self {
+        self.description = Some(description);
+        self
+    }
+
+    pub fn with_public(mut self, public: bool) -> Self {
+        self.public = public;
+        self
+    }
+
+    pub fn encode(
+        &mut self,
+        input: &[u8],
+        output: &mut Vec<u8>,
+        digest: &mut Digest,
+        available: &mut HashSet<String>,
+    ) -> Result<Option<Vec<u8>>, Utf8Error> {
+        if self.incomplete.is_empty() {
+            self.encode_utf8(input, output, digest, available)
+        } else {
+            let mut incomplete = std::mem::take(&mut self.incomplete);
+            incomplete.extend_from_slice(input);
+            self.encode_utf8(&incomplete, output, digest, available)
+        }
+    }
+
+    fn encode_utf8(
+        &mut self,
+        input: &[u8],
+        output: &mut Vec<u8>,
+        digest: &mut Digest,
+        available: &mut HashSet<String>,
+    ) -> Result<Option<Vec<u8>>, Utf8Error> {
+        let (valid, incomplete) = match str::from_utf8(input) {
+            Ok(input) => (input, &[][..]),
+            Err(err) if err.error_len().is_none() => {
+                let (valid, incomplete) = input.split_at(err.valid_up_to());
+                (unsafe { str::from_utf8_unchecked(valid) }, incomplete)
+            }
+            Err(err) => return Err(err),
+        };
+        match self.encode_str(valid, output, digest, available) {
+            None => {
+                self.incomplete.extend_from_slice(incomplete);
+                Ok(None)
+            }
+            Some(overflow) => {
+                let mut overflow = overflow.as_bytes().to_vec();
+                overflow.extend_from_slice(incomplete);
+                Ok(Some(overflow))
+            }
+        }
+    }
+
+    fn encode_str<'a>(
+        &mut self,
+        mut input: &'a str,
+        output: &mut Vec<u8>,
+        digest: &mut Digest,
+        available: &mut HashSet<String>,
+    ) -> Option<&'a str> {
+        while !input.is_empty() {
+            if self.chunk.is_empty() && self.written.len() == self.max_files {
+                return Some(input);
+            }
+            let (chunk_end, is_complete) = self.chunker.split(input);
+            let (chunk, remainder) = input.split_at(chunk_end);
+            digest.update(chunk);
+            self.chunk.push_str(chunk);
+            if is_complete {
+                self.end_chunk(output, digest, available);
+            }
+            input = remainder;
+        }
+        None
+    }
+
+    fn end_chunk(
+        &mut self,
+        output: &mut Vec<u8>,
+        digest: &mut Digest,
+        available: &mut HashSet<String>,
+    ) {
+        let name = digest.end_chunk();
+        if !available.contains(name) {
+            available.insert(name.to_owned());
+            self.open_files(output);
+            if !self.written.is_empty() {
+                output.push(b',');
+            }
+            output.push(b'"');
+            output.extend_from_slice(name.as_bytes());
+            output.extend_from_slice(br#"":{"content":""#);
+            escape(&self.chunk, output);
+            output.extend_from_slice(br#""}"#);
+            self.written.push(name.to_owned());
+        }
+        self.chunk.clear();
+    }
+
+    fn open_files(&mut self, output: &mut Vec<u8>) {
+        if self.opened {
+            return;
+        }
+        self.opened = true;
+        output.push(b'{');
+        if let Some(description) = self.description {
+            output.extend_from_slice(br#""description":""#);
+            escape(description, output);
+            output.extend_from_slice(br#"","#);
+        }
+        if self.public {
+            output.extend_from_slice(br#""public":true,"#);
+        }
+        output.extend_from_slice(br#""files":{"#);
+    }
+
+    pub fn close(
+        &mut self,
+        output: &mut Vec<u8>,
+        digest: &mut Digest,
+        available: &mut HashSet<String>,
+    ) -> Result<(), Utf8Error> {
+        if !self.incomplete.is_empty() {
+            return str::from_utf8(&self.incomplete).map(|_| ());
+        }
+        if !self.chunk.is_empty() {
+            self.end_chunk(output, digest, available);
+        }
+        self.open_files(output);
+        Ok(())
+    }
+
+    pub fn finish(
+        self,
+        removed: &[String],
+        manifest: Option<&str>,
+        output: &mut Vec<u8>,
+    ) -> Vec<String> {
+        let mut has_entries = !self.written.is_empty();
+        for name in removed {
+            if has_entries {
+                output.push(b',');
+            }
+            output.push(b'"');
+            escape(name, output);
+            output.extend_from_slice(br#"":null"#);
+            has_entries = true;
+        }
+        if let Some(manifest) = manifest {
+            if has_entries {
+                output.push(b',');
+            }
+            output.push(b'"');
+            escape(format::MANIFEST_FILENAME, output);
+            output.extend_from_slice(br#"":{"content":""#);
+            escape(manifest, output);
+            output.extend_from_slice(br#""}"#);
+        }
+        output.extend_from_slice(b"}}");
+        self.written
+    }
+}
+
+fn escape(input: &str, output: &mut Vec<u8>) {
+    const HEX: &[u8; 16] = b"0123456789abcdef";
+    let bytes = input.as_bytes();
+    let mut unicode_escape = *br"\u0000";
+    let mut start = 0;
+    for (index, &byte) in bytes.iter().enumerate() {
+        let escaped: &[u8] = match byte {
+            b'"' => br#"\""#,
+            b'\\' => br"\\",
+            b'\n' => br"\n",
+            b'\r' => br"\r",
+            b'\t' => br"\t",
+            0x08 => br"\b",
+            0x0c => br"\f",
+            0x00..=0x1f => {
+                unicode_escape[4] = HEX[(byte >> 4) as usize];
+                unicode_escape[5] = HEX[(byte & 0xf) as usize];
+                &unicode_escape
+            }
+            _ => continue,
+        };
+        output.extend_from_slice(&bytes[start..index]);
+        output.extend_from_slice(escaped);
+        start = index + 1;
+    }
+    output.extend_from_slice(&bytes[start..]);
+}
@@ -0,0 +1,46 @@
+use super::Digest;
+use crate::format;
+use serde::{ser::SerializeMap as _, Serialize, Serializer};
+
+pub struct Manifest<'a> {
+    pub children: &'a [String],
+    pub files: &'a [&'a str],
+    pub digest: &'a Digest,
+    pub expires_at: Option<u64>,
+}
+
+impl<'a> Serialize for Manifest<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let chunk_sha256 = self.digest.chunk_sha256();
+        let mut map = serializer.serialize_map(Some(
+            7 + usize::from(!self.children.is_empty())
+                + usize::from(!chunk_sha256.is_empty())
+                + usize::from(self.expires_at.is_some()),
+        ))?;
+        map.serialize_entry("version", &format::FORMAT_VERSION)?;
+        map.serialize_entry("encoding", format::ENCODING)?;
+        map.serialize_entry("compression", format::COMPRESSION)?;
+        if !self.children.is_empty() {
+            map.serialize_entry("children", self.children)?;
+        }
+        if !self.files.is_empty() {
+            map.serialize_entry("files", self.files)?;
+        }
+        if self.files.is_empty() {
+            map.serialize_entry("names", self.digest.names())?;
+        }
+        map.serialize_entry("chunks", &self.digest.chunks_len())?;
+        map.serialize_entry("len", &self.digest.len())?;
+        map.serialize_entry("sha256", &self.digest.sha256())?;
+        if !chunk_sha256.is_empty() {
+            map.serialize_entry("chunk_sha256", chunk_sha256)?;
+        }
+        if let Some(expires_at) = self.expires_at {
+            map.serialize_entry("expires_at", &expires_at)?;
+        }
+        map.end()
+    }
+}
@@ -0,0 +1,37 @@
+use serde::{ser::SerializeMap as _, Serialize, Serializer};
+use std::collections::BTreeMap;
+
+pub enum RecordContent<'a> {
+    Value(&'a str),
+    Files(&'a BTreeMap<String, String>),
+}
+
+pub struct Record<'a> {
+    pub id: &'a str,
+    pub public: bool,
+    pub created_at: &'a str,
+    pub updated_at: &'a str,
+    pub expires_at: Option<u64>,
+    pub content: RecordContent<'a>,
+}
+
+impl<'a> Serialize for Record<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(5 + usize::from(self.expires_at.is_some())))?;
+        map.serialize_entry("id", self.id)?;
+        map.serialize_entry("public", &self.public)?;
+        map.serialize_entry("created_at", self.created_at)?;
+        map.serialize_entry("updated_at", self.updated_at)?;
+        if let Some(expires_at) = self.expires_at {
+            map.serialize_entry("expires_at", &expires_at)?;
+        }
+        match self.content {
+            RecordContent::Value(value) => map.serialize_entry("value", value)?,
+            RecordContent::Files(files) => map.serialize_entry("files", files)?,
+        }
+        map.end()
+    }
+}
@@ -0,0 +1,39 @@
+use serde::{ser::SerializeMap as _, Serialize, Serializer};
+
+struct RemovedFiles<'a>(&'a [String]);
+
+impl<'a> Serialize for RemovedFiles<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for filename in self.0 {
+            map.serialize_entry(filename, &())?;
+        }
+        map.end()
+    }
+}
+
+pub struct Removal<'a> {
+    filenames: RemovedFiles<'a>,
+}
+
+impl<'a> Serialize for Removal<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(1))?;
+        map.serialize_entry("files", &self.filenames)?;
+        map.end()
+    }
+}
+
+impl<'a> Removal<'a> {
+    pub fn new(filenames: &'a [String]) -> Self {
+        Self {
+            filenames: RemovedFiles(filenames),
+        }
+    }
+}
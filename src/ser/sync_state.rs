@@ -0,0 +1,52 @@
+use serde::{ser::SerializeMap as _, Serialize, Serializer};
+
+pub struct SyncEntry<'a> {
+    pub id: &'a str,
+    pub sha256: &'a str,
+    pub revision: &'a str,
+}
+
+impl<'a> Serialize for SyncEntry<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(3))?;
+        map.serialize_entry("id", self.id)?;
+        map.serialize_entry("sha256", self.sha256)?;
+        map.serialize_entry("revision", self.revision)?;
+        map.end()
+    }
+}
+
+struct SyncEntries<'a>(&'a [(&'a str, SyncEntry<'a>)]);
+
+impl<'a> Serialize for SyncEntries<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for (key, entry) in self.0 {
+            map.serialize_entry(key, entry)?;
+        }
+        map.end()
+    }
+}
+
+pub struct SyncState<'a> {
+    pub namespace: &'a str,
+    pub entries: &'a [(&'a str, SyncEntry<'a>)],
+}
+
+impl<'a> Serialize for SyncState<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(2))?;
+        map.serialize_entry("namespace", self.namespace)?;
+        map.serialize_entry("entries", &SyncEntries(self.entries))?;
+        map.end()
+    }
+}
@@ -0,0 +1,305 @@
+use crate::{
+    stash::{Cache, Error, StatusCode},
+    Stash,
+};
+use futures_util::StreamExt as _;
+use hyper::{
+    body::{HttpBody as _, Sender},
+    header::{self, HeaderValue},
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server,
+};
+use std::{convert::Infallible, future::Future, mem, net::SocketAddr, sync::Arc, time::Duration};
+use tokio::sync::oneshot;
+
+const COLLECTION_METHODS: &str = "GET, POST";
+const ENTRY_METHODS: &str = "GET, PUT, POST, DELETE";
+
+const MAX_BODY_BYTES: usize = 32 * 1024 * 1024;
+
+const CACHE_BYTES: usize = 64 * 1024 * 1024;
+
+const MAX_ATTEMPTS: u32 = 3;
+
+const MAX_RETRY_WAIT: Duration = Duration::from_secs(5);
+
+pub async fn serve(stash: Stash, addr: SocketAddr, token: Option<&str>) -> Result<(), Error> {
+    serve_with_shutdown(stash, addr, token, std::future::pending()).await
+}
+
+pub async fn serve_with_shutdown<F>(
+    stash: Stash,
+    addr: SocketAddr,
+    token: Option<&str>,
+    signal: F,
+) -> Result<(), Error>
+where
+    F: Future<Output = ()>,
+{
+    let token: Option<Arc<str>> = match token {
+        Some("") => return Err(Error::from_invalid("bearer token is empty")),
+        Some(token) => Some(token.into()),
+        None if addr.ip().is_loopback() => None,
+        None => {
+            return Err(Error::from_invalid(
+                "a bearer token is required to serve a non-loopback address",
+            ))
+        }
+    };
+    let stash = match stash.cache_stats() {
+        Some(_) => stash,
+        None => stash.with_cache(Cache::bytes(CACHE_BYTES)),
+    };
+    let make_service = make_service_fn(move |_| {
+        let stash = stash.clone();
+        let token = token.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                let stash = stash.clone();
+                let token = token.clone();
+                async move {
+                    Ok::<_, Infallible>(if is_authorized(token.as_deref(), &request) {
+                        handle(&stash, request).await
+                    } else {
+                        unauthorized()
+                    })
+                }
+            }))
+        }
+    });
+    Server::try_bind(&addr)
+        .map_err(Error::from_hyper)?
+        .serve(make_service)
+        .with_graceful_shutdown(signal)
+        .await
+        .map_err(Error::from_hyper)
+}
+
+fn is_authorized(token: Option<&str>, request: &Request<Body>) -> bool {
+    let Some(token) = token else {
+        return true;
+    };
+    let Some((scheme, credentials)) = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split_once(' '))
+    else {
+        return false;
+    };
+    let credentials = credentials.trim().as_bytes();
+    scheme.eq_ignore_ascii_case("bearer")
+        && credentials.len() == token.len()
+        && credentials
+            .iter()
+            .zip(token.as_bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+async fn handle(stash: &Stash, request: Request<Body>) -> Response<Body> {
+    let path = request.uri().path().to_owned();
+    match path.strip_prefix("/kv") {
+        Some("" | "/") => collection(stash, request).await,
+        Some(rest) => match rest.strip_prefix('/') {
+            Some(id) if !id.contains('/') => entry(stash, id, request).await,
+            _ => respond(StatusCode::NOT_FOUND, "not found"),
+        },
+        None => respond(StatusCode::NOT_FOUND, "not found"),
+    }
+}
+
+async fn collection(stash: &Stash, request: Request<Body>) -> Response<Body> {
+    match *request.method() {
+        Method::GET => list(stash).await,
+        Method::POST => {
+            let value = match body(request).await {
+                Ok(value) => value,
+                Err(response) => return response,
+            };
+            match stash.insert(&value).await {
+                Ok(id) => {
+                    let mut response = respond(StatusCode::CREATED, id.clone());
+                    if let Ok(location) = HeaderValue::from_str(&format!("/kv/{id}")) {
+                        response.headers_mut().insert(header::LOCATION, location);
+                    }
+                    response
+                }
+                Err(err) => respond_err(&err),
+            }
+        }
+        _ => not_allowed(COLLECTION_METHODS),
+    }
+}
+
+async fn entry(stash: &Stash, id: &str, request: Request<Body>) -> Response<Body> {
+    match *request.method() {
+        Method::GET => match retry(|| stash.get(id)).await {
+            Ok(value) => respond(StatusCode::OK, value),
+            Err(err) => respond_err(&err),
+        },
+        Method::PUT | Method::POST => {
+            let value = match body(request).await {
+                Ok(value) => value,
+                Err(response) => return response,
+            };
+            match retry(|| stash.set(id, &value)).await {
+                Ok(()) => respond(StatusCode::NO_CONTENT, Body::empty()),
+                Err(err) => respond_err(&err),
+            }
+        }
+        Method::DELETE => match retry(|| stash.remove(id)).await {
+            Ok(()) => respond(StatusCode::NO_CONTENT, Body::empty()),
+            Err(err) => respond_err(&err),
+        },
+        _ => not_allowed(ENTRY_METHODS),
+    }
+}
+
+async fn list(stash: &Stash) -> Response<Body> {
+    let (ready, started) = oneshot::channel();
+    let (sender, body) = Body::channel();
+    tokio::spawn(send_ids(stash.clone(), ready, sender));
+    match started.await {
+        Ok(Ok(())) => {
+            let mut response = Response::new(body);
+            response.headers_mut().insert(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("application/json"),
+            );
+            response
+        }
+        Ok(Err(err)) => respond_err(&err),
+        Err(err) => respond(StatusCode::INTERNAL_SERVER_ERROR, err.to_string()),
+    }
+}
+
+async fn send_ids(stash: Stash, ready: oneshot::Sender<Result<(), Error>>, mut sender: Sender) {
+    let mut chunks = stash.ids();
+    let mut chunk = match chunks.next().await.transpose() {
+        Ok(chunk) => chunk,
+        Err(err) => {
+            let _ = ready.send(Err(err));
+            return;
+        }
+    };
+    if ready.send(Ok(())).is_err() {
+        return;
+    }
+    let mut json = b"[".to_vec();
+    let mut empty = true;
+    while let Some(ids) = chunk {
+        for id in ids {
+            if !mem::replace(&mut empty, false) {
+                json.push(b',');
+            }
+            if serde_json::to_writer(&mut json, &id).is_err() {
+                return sender.abort();
+            }
+        }
+        if sender.send_data(mem::take(&mut json).into()).await.is_err() {
+            return;
+        }
+        chunk = match chunks.next().await.transpose() {
+            Ok(chunk) => chunk,
+            Err(_) => return sender.abort(),
+        };
+    }
+    json.push(b']');
+    let _ = sender.send_data(json.into()).await;
+}
+
+async fn body(request: Request<Body>) -> Result<String, Response<Body>> {
+    let too_large = || {
+        respond(
+            StatusCode::PAYLOAD_TOO_LARGE,
+            format!("body exceeds {MAX_BODY_BYTES} bytes"),
+        )
+    };
+    let mut body = request.into_body();
+    if body.size_hint().lower() > MAX_BODY_BYTES as u64 {
+        return Err(too_large());
+    }
+    let mut bytes = Vec::new();
+    while let Some(data) = body.data().await {
+        let data = data.map_err(|err| respond(StatusCode::BAD_REQUEST, err.to_string()))?;
+        if bytes.len() + data.len() > MAX_BODY_BYTES {
+            return Err(too_large());
+        }
+        bytes.extend_from_slice(&data);
+    }
+    String::from_utf8(bytes).map_err(|_| respond(StatusCode::BAD_REQUEST, "body is not utf-8"))
+}
+
+async fn retry<T, F, R>(mut request: F) -> Result<T, Error>
+where
+    F: FnMut() -> R,
+    R: Future<Output = Result<T, Error>>,
+{
+    let mut attempt = 1;
+    loop {
+        let err = match request().await {
+            Ok(value) => return Ok(value),
+            Err(err) => err,
+        };
+        let wait = match err.retry_after() {
+            Some(retry_after) => retry_after,
+            None if err.status().is_some_and(|status| status.is_server_error()) => {
+                Duration::from_millis(250 << attempt)
+            }
+            None => return Err(err),
+        };
+        if attempt >= MAX_ATTEMPTS || wait > MAX_RETRY_WAIT {
+            return Err(err);
+        }
+        tokio::time::sleep(wait).await;
+        attempt += 1;
+    }
+}
+
+fn respond(status: StatusCode, body: impl Into<Body>) -> Response<Body> {
+    let mut response = Response::new(body.into());
+    *response.status_mut() = status;
+    if status != StatusCode::NO_CONTENT {
+        response.headers_mut().insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("text/plain; charset=utf-8"),
+        );
+    }
+    response
+}
+
+fn respond_err(err: &Error) -> Response<Body> {
+    let status = match err.status() {
+        _ if err.is_rate_limited() => StatusCode::TOO_MANY_REQUESTS,
+        Some(StatusCode::NOT_FOUND) => StatusCode::NOT_FOUND,
+        Some(StatusCode::TOO_MANY_REQUESTS) => StatusCode::TOO_MANY_REQUESTS,
+        _ if err.is_invalid() => StatusCode::BAD_REQUEST,
+        _ if err.is_foreign() || err.is_bundle() || err.is_not_bundle() => StatusCode::CONFLICT,
+        _ => StatusCode::BAD_GATEWAY,
+    };
+    let mut response = respond(status, err.to_string());
+    if let Some(retry_after) = err.retry_after() {
+        let seconds = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+        response
+            .headers_mut()
+            .insert(header::RETRY_AFTER, HeaderValue::from(seconds.max(1)));
+    }
+    response
+}
+
+fn unauthorized() -> Response<Body> {
+    let mut response = respond(StatusCode::UNAUTHORIZED, "missing or invalid bearer token");
+    response
+        .headers_mut()
+        .insert(header::WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+    response
+}
+
+fn not_allowed(methods: &'static str) -> Response<Body> {
+    let mut response = respond(StatusCode::METHOD_NOT_ALLOWED, "method not allowed");
+    response
+        .headers_mut()
+        .insert(header::ALLOW, HeaderValue::from_static(methods));
+    response
+}
@@ -1,167 +1,1049 @@
+mod cache;
+mod check;
+mod chunks;
+mod disk_cache;
 mod error;
+mod etags;
+mod flights;
 mod futures;
+mod gc;
 mod http;
+mod import;
+mod listing;
+mod lru;
+mod metadata;
+mod pager;
+mod parts;
+mod rate_limit;
+mod raw_urls;
+mod read_only;
+mod sync;
+mod trash;
+mod watch;
 
+pub use cache::{Cache, CacheStats};
+pub use check::{CheckReport, EntryCheck, Health};
+pub use chunks::{Chunks, ChunksReader};
+pub use disk_cache::{DiskCache, Freshness};
 pub use error::Error;
-pub use hyper::StatusCode;
+pub use flights::FlightStats;
+pub use gc::GcReport;
+pub use hyper::{body::Bytes, StatusCode};
+pub use import::ImportReport;
+pub use listing::{Listed, Listing};
+pub use metadata::{Metadata, Visibility};
+pub use rate_limit::RateLimit;
+pub use read_only::ReadOnlyStash;
+pub use sync::{SyncAction, SyncChange, SyncReport};
+pub use trash::Trashed;
+pub use watch::{Event, Watch};
 
-use crate::{de, ser, Auth};
-use futures::ids_chunk::IdsChunkFuture;
+use crate::{
+    de,
+    format::{self, GIST_MAX_CHUNKS},
+    ser, Auth, Chunking,
+};
+use etags::{Conditional, Etags};
+use futures::{
+    files_body::{FilesBody, Source},
+    ids_chunk::IdsChunkFuture,
+    reader::ReaderStream,
+};
 use futures_core::{Future, Stream};
+use futures_util::{future, stream, StreamExt as _, TryStreamExt as _};
+use hyper::header::HeaderValue;
+use pager::{Pager, PAGE_SIZE};
 use std::{
+    collections::BTreeMap,
     fmt::{self, Debug},
     hint::unreachable_unchecked,
     mem,
+    ops::{Bound, RangeBounds},
+    path::Path,
     pin::Pin,
+    sync::{Arc, Mutex},
     task::{self, Poll},
+    time::Duration,
 };
+use tokio::io::{AsyncBufReadExt as _, AsyncRead, AsyncWrite, AsyncWriteExt as _, BufReader};
+
+#[derive(Clone, Copy)]
+struct Config {
+    verify: bool,
+    chunk_hashes: bool,
+    chunking: Chunking,
+    strict: bool,
+    raw_reads: bool,
+    raw_reads_ttl: Duration,
+    etags: bool,
+    watch_interval: Duration,
+    gc_concurrency: usize,
+    check_concurrency: usize,
+    transfer_concurrency: usize,
+    soft_delete: bool,
+}
 
 #[derive(Clone)]
-#[repr(transparent)]
-pub struct Stash(http::Client);
+pub struct Stash {
+    client: http::Client,
+    config: Config,
+    raw_urls: raw_urls::RawUrls,
+    etags: Etags,
+    cache: Option<Arc<Mutex<cache::Values>>>,
+    disk_cache: Option<Arc<DiskCache>>,
+}
 
 impl Debug for Stash {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Stash")
-            .field("auth", &self.0.auth())
-            .field("hyper", &self.0.hyper())
+            .field("auth", &self.client.auth())
+            .field("hyper", &self.client.hyper())
+            .field("verify", &self.config.verify)
+            .field("chunk_hashes", &self.config.chunk_hashes)
+            .field("chunking", &self.config.chunking)
+            .field("strict", &self.config.strict)
+            .field("raw_reads", &self.config.raw_reads)
+            .field("raw_reads_ttl", &self.config.raw_reads_ttl)
+            .field("etags", &self.config.etags)
+            .field("watch_interval", &self.config.watch_interval)
+            .field("gc_concurrency", &self.config.gc_concurrency)
+            .field("check_concurrency", &self.config.check_concurrency)
+            .field("transfer_concurrency", &self.config.transfer_concurrency)
+            .field("soft_delete", &self.config.soft_delete)
+            .field("cache", &self.cache_stats())
+            .field("disk_cache", &self.disk_cache)
             .finish()
     }
 }
 
 impl Stash {
     pub fn new(auth: Auth) -> Self {
-        Stash(http::Client::new(auth.into_header_value()))
+        Self::with_client(http::Client::new(Some(auth)))
+    }
+
+    fn with_client(client: http::Client) -> Self {
+        Stash {
+            client,
+            config: Config {
+                verify: true,
+                chunk_hashes: false,
+                chunking: Chunking::default(),
+                strict: false,
+                raw_reads: false,
+                raw_reads_ttl: Duration::from_secs(60),
+                etags: false,
+                watch_interval: Duration::from_secs(60),
+                gc_concurrency: 8,
+                check_concurrency: 8,
+                transfer_concurrency: 8,
+                soft_delete: false,
+            },
+            raw_urls: raw_urls::RawUrls::default(),
+            etags: Etags::default(),
+            cache: None,
+            disk_cache: None,
+        }
+    }
+
+    pub fn with_verify(mut self, verify: bool) -> Self {
+        self.config.verify = verify;
+        self
+    }
+
+    pub fn with_chunking(mut self, chunking: Chunking) -> Self {
+        self.config.chunking = chunking;
+        self
+    }
+
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.config.strict = strict;
+        self
+    }
+
+    pub fn with_raw_reads(mut self, raw_reads: bool) -> Self {
+        self.config.raw_reads = raw_reads;
+        self
+    }
+
+    pub fn with_raw_reads_ttl(mut self, raw_reads_ttl: Duration) -> Self {
+        self.config.raw_reads_ttl = raw_reads_ttl;
+        self
+    }
+
+    pub fn with_etags(mut self, etags: bool) -> Self {
+        self.config.etags = etags;
+        self
+    }
+
+    pub fn with_watch_interval(mut self, watch_interval: Duration) -> Self {
+        self.config.watch_interval = watch_interval;
+        self
+    }
+
+    pub fn with_gc_concurrency(mut self, gc_concurrency: usize) -> Self {
+        self.config.gc_concurrency = gc_concurrency.max(1);
+        self
+    }
+
+    pub fn with_check_concurrency(mut self, check_concurrency: usize) -> Self {
+        self.config.check_concurrency = check_concurrency.max(1);
+        self
+    }
+
+    pub fn with_transfer_concurrency(mut self, transfer_concurrency: usize) -> Self {
+        self.config.transfer_concurrency = transfer_concurrency.max(1);
+        self
+    }
+
+    pub fn with_soft_delete(mut self, soft_delete: bool) -> Self {
+        self.config.soft_delete = soft_delete;
+        self
+    }
+
+    pub fn with_cache(mut self, cache: Cache) -> Self {
+        self.cache = Some(Arc::new(Mutex::new(cache::Values::new(cache))));
+        self
+    }
+
+    pub fn with_disk_cache(mut self, disk_cache: DiskCache) -> Self {
+        self.disk_cache = Some(Arc::new(disk_cache));
+        self
+    }
+
+    pub fn cache_stats(&self) -> Option<CacheStats> {
+        self.cache().map(|cache| cache.stats())
+    }
+
+    pub fn flight_stats(&self) -> FlightStats {
+        self.client.flight_stats()
+    }
+
+    pub fn with_chunk_hashes(mut self, chunk_hashes: bool) -> Self {
+        self.config.chunk_hashes = chunk_hashes;
+        self
     }
 
     pub fn auth(&self) -> &Auth {
-        unsafe { mem::transmute(self.0.auth()) }
+        self.client
+            .auth()
+            .expect("a Stash is always constructed with an Auth")
     }
 
     pub async fn insert(&self, value: &str) -> Result<String, Error> {
-        let resp = self
-            .0
-            .gists()
-            .into_request(
-                hyper::Method::POST,
-                unsafe { serde_json::to_vec(&ser::Files::new(value, 0)).unwrap_unchecked() }.into(),
-            )
-            .map_err(Error::from_http)?
+        self.insert_with_visibility(value, Visibility::Secret).await
+    }
+
+    pub async fn insert_with_visibility(
+        &self,
+        value: &str,
+        visibility: Visibility,
+    ) -> Result<String, Error> {
+        self.insert_with(value, visibility, None).await
+    }
+
+    pub async fn insert_with_ttl(&self, value: &str, ttl: Duration) -> Result<String, Error> {
+        self.insert_with(
+            value,
+            Visibility::Secret,
+            Some(format::unix_time().saturating_add(ttl.as_secs())),
+        )
+        .await
+    }
+
+    async fn insert_with(
+        &self,
+        value: &str,
+        visibility: Visibility,
+        expires_at: Option<u64>,
+    ) -> Result<String, Error> {
+        let public = visibility == Visibility::Public;
+        let chunks = ser::chunks(value, self.config.chunking);
+        let digest = ser::Digest::from_chunks(&chunks, self.config.chunk_hashes);
+        let layout = ser::Layout::new(&chunks, digest.names(), &[]);
+        let children = self.write_children(&layout.children, public).await?;
+        let manifest = ser::Manifest {
+            children: &children,
+            files: &[],
+            digest: &digest,
+            expires_at,
+        };
+        let result = self
+            .create(unsafe {
+                serde_json::to_vec(
+                    &ser::Files::new(&layout.root)
+                        .with_public(public)
+                        .with_manifest(manifest),
+                )
+                .unwrap_unchecked()
+            })
+            .await;
+        if result.is_err() {
+            self.remove_gists(&children).await.ok();
+        }
+        result
+    }
+
+    pub async fn insert_stream<S, E>(&self, stream: S) -> Result<String, Error>
+    where
+        S: Stream<Item = Result<Bytes, E>> + Send + 'static,
+        E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+    {
+        let source = Source::new(
+            stream,
+            self.config.chunking,
+            self.config.chunk_hashes,
+            None,
+            Vec::new(),
+        );
+        let id = self
+            .create(FilesBody::root(source.clone(), GIST_MAX_CHUNKS))
+            .await?;
+        let result = async {
+            let children = self.write_stream_children(&source, false).await?;
+            if let Some(body) = Source::manifest_update(&source, &children) {
+                let client = self.client.gist(&id).map_err(Error::from_http)?;
+                if let Err(err) = Self::update(client, body).await {
+                    self.remove_gists(&children).await.ok();
+                    return Err(err);
+                }
+            }
+            Ok(())
+        }
+        .await;
+        match result {
+            Ok(()) => Ok(id),
+            Err(err) => {
+                self.remove_gists(&[id]).await.ok();
+                Err(err)
+            }
+        }
+    }
+
+    pub async fn insert_reader<R>(&self, reader: R) -> Result<String, Error>
+    where
+        R: AsyncRead + Send + 'static,
+    {
+        self.insert_stream(ReaderStream::new(reader)).await
+    }
+
+    pub async fn insert_files(&self, files: &BTreeMap<String, String>) -> Result<String, Error> {
+        self.insert_files_with(files, Visibility::Secret, None)
             .await
-            .map_err(Error::from_hyper)?;
-        if resp.status() == hyper::StatusCode::CREATED {
-            serde_json::from_slice::<de::Id>(
-                &futures::body::SliceFuture::from(resp.into_body())
-                    .await
-                    .map_err(Error::from_hyper)?,
+    }
+
+    async fn insert_files_with(
+        &self,
+        files: &BTreeMap<String, String>,
+        visibility: Visibility,
+        expires_at: Option<u64>,
+    ) -> Result<String, Error> {
+        if files.is_empty() {
+            return Err(Error::from_invalid("bundle has no files"));
+        }
+        if files.keys().any(String::is_empty) {
+            return Err(Error::from_invalid("file name is empty"));
+        }
+        if files.keys().any(|name| name.contains('/')) {
+            return Err(Error::from_invalid("file name contains `/`"));
+        }
+        let mut digest = ser::Digest::new(self.config.chunk_hashes);
+        let files = files
+            .iter()
+            .map(|(name, value)| {
+                let chunks = ser::chunks(value, self.config.chunking);
+                for chunk in &chunks {
+                    digest.update(chunk);
+                    digest.end_chunk();
+                }
+                (name.as_str(), chunks)
+            })
+            .collect::<Vec<_>>();
+        if digest.chunks_len() > GIST_MAX_CHUNKS {
+            return Err(Error::from_invalid("files exceed a single gist"));
+        }
+        let names = files.iter().map(|(name, _)| *name).collect::<Vec<_>>();
+        let manifest = ser::Manifest {
+            children: &[],
+            files: &names,
+            digest: &digest,
+            expires_at,
+        };
+        self.create(unsafe {
+            serde_json::to_vec(
+                &ser::Bundle::new(&files, &manifest).with_public(visibility == Visibility::Public),
             )
-            .map(String::from)
-            .map_err(Error::from_json)
-        } else {
-            Err(Error::from_status(resp.status()))
+            .unwrap_unchecked()
+        })
+        .await
+    }
+
+    pub async fn get_files(&self, id: &str) -> Result<BTreeMap<String, String>, Error> {
+        let root = self.fetch(id).await?;
+        self.bundle_files(&root)
+    }
+
+    fn bundle_files(&self, root: &[u8]) -> Result<BTreeMap<String, String>, Error> {
+        let mut bundle = serde_json::from_slice::<de::Bundle>(root).map_err(Error::from_json)?;
+        Self::check_trashed(bundle.trashed_at())?;
+        let Some(manifest) = bundle
+            .take_manifest()
+            .filter(|manifest| !manifest.files.is_empty())
+        else {
+            return Err(Error::from_not_bundle());
+        };
+        self.check_foreign(bundle.foreign(&manifest.files))?;
+        let chunks_len = bundle.chunks_len();
+        let mut files = BTreeMap::from(bundle);
+        for name in &manifest.files {
+            files.entry(name.clone()).or_default();
+        }
+        if self.config.verify {
+            let mut digest = ser::Digest::new(false);
+            for value in files.values() {
+                digest.update(value);
+            }
+            manifest
+                .check_chunks(chunks_len)
+                .and_then(|()| manifest.check_len(digest.len()))
+                .and_then(|()| manifest.check_sha256(&digest.sha256()))
+                .map_err(Error::from_integrity)?;
         }
+        Ok(files)
     }
 
     pub async fn get(&self, id: &str) -> Result<String, Error> {
-        let resp = self
-            .0
-            .gist(id)
-            .map_err(Error::from_http)?
-            .into_request(hyper::Method::GET, hyper::Body::empty())
-            .map_err(Error::from_http)?
-            .await
-            .map_err(Error::from_hyper)?;
-        if resp.status() == hyper::StatusCode::OK {
-            serde_json::from_slice::<de::Files>(
-                &futures::body::SliceFuture::from(resp.into_body())
-                    .await
-                    .map_err(Error::from_hyper)?,
-            )
-            .map(String::from)
-            .map_err(Error::from_json)
-        } else {
-            Err(Error::from_status(resp.status()))
+        if self.disk_cache.is_some() {
+            return self.get_with_freshness(id).await.map(|(value, _)| value);
         }
+        self.get_cached(id).await
     }
 
-    pub async fn set(&self, id: &str, value: &str) -> Result<(), Error> {
-        let client = self.0.gist(id).map_err(Error::from_http)?;
-        let current_len = {
-            let resp = client
-                .request(hyper::Method::GET, hyper::Body::empty())
-                .map_err(Error::from_http)?
+    async fn get_cached(&self, id: &str) -> Result<String, Error> {
+        let generation = match self.cache() {
+            None => None,
+            Some(mut cache) => match cache.get(id) {
+                Some(value) => return Ok(value.as_ref().to_owned()),
+                None => Some(cache.generation()),
+            },
+        };
+        let (value, expires_at) = self.get_uncached(id).await?;
+        if let (Some(mut cache), Some(generation)) = (self.cache(), generation) {
+            cache.insert(
+                id.to_owned(),
+                Arc::from(value.as_str()),
+                expires_at,
+                generation,
+            );
+        }
+        Ok(value)
+    }
+
+    async fn get_uncached(&self, id: &str) -> Result<(String, Option<u64>), Error> {
+        if self.config.raw_reads {
+            return self.get_raw(id).await;
+        }
+        let root = self.fetch(id).await?;
+        self.assemble(&root).await
+    }
+
+    pub async fn get_with_freshness(&self, id: &str) -> Result<(String, Freshness), Error> {
+        let Some(disk_cache) = &self.disk_cache else {
+            return self
+                .get_cached(id)
                 .await
-                .map_err(Error::from_hyper)?;
-            if resp.status() == hyper::StatusCode::OK {
-                serde_json::from_slice::<de::FilesLen>(
-                    &futures::body::SliceFuture::from(resp.into_body())
-                        .await
-                        .map_err(Error::from_hyper)?,
-                )
-                .map_err(Error::from_json)?
-                .into()
-            } else {
-                return Err(Error::from_status(resp.status()));
-            }
+                .map(|value| (value, Freshness::Fresh));
         };
-        let resp = client
-            .into_request(
-                hyper::Method::PATCH,
-                unsafe {
-                    serde_json::to_vec(&ser::Files::new(value, current_len)).unwrap_unchecked()
+        let generation = match self.cache() {
+            None => None,
+            Some(mut cache) => match cache.get(id) {
+                Some(value) => return Ok((value.as_ref().to_owned(), Freshness::Fresh)),
+                None => Some(cache.generation()),
+            },
+        };
+        let cached = disk_cache.read(id).await;
+        let etag = cached
+            .as_ref()
+            .and_then(|entry| HeaderValue::from_str(&entry.etag).ok())
+            .filter(|etag| !etag.is_empty());
+        let client = self.client.gist(id).map_err(Error::from_http)?;
+        let err = match Self::revalidate(&client, etag).await {
+            Ok(None) => match cached {
+                Some(ref entry) if entry.is_expired() => Error::from_status(StatusCode::NOT_FOUND),
+                Some(entry) => return Ok((entry.value, Freshness::Fresh)),
+                None => Error::from_status(StatusCode::NOT_MODIFIED),
+            },
+            Ok(Some((root, etag))) => match self.assemble(&root).await {
+                Ok((value, expires_at)) => {
+                    if let Ok(de::Revision(revision)) = serde_json::from_slice(&root) {
+                        disk_cache
+                            .write(
+                                id,
+                                &revision,
+                                etag.as_ref().and_then(|etag| etag.to_str().ok()),
+                                expires_at,
+                                &value,
+                            )
+                            .await
+                            .ok();
+                    }
+                    if let (Some(mut cache), Some(generation)) = (self.cache(), generation) {
+                        cache.insert(
+                            id.to_owned(),
+                            Arc::from(value.as_str()),
+                            expires_at,
+                            generation,
+                        );
+                    }
+                    return Ok((value, Freshness::Fresh));
                 }
-                .into(),
+                Err(err) => err,
+            },
+            Err(err) => err,
+        };
+        match cached {
+            _ if err.status() == Some(StatusCode::NOT_FOUND) => {
+                disk_cache.remove(id).await;
+                Err(err)
+            }
+            Some(entry)
+                if disk_cache.stale_if_error()
+                    && !entry.is_expired()
+                    && !err.is_integrity()
+                    && !err.is_foreign()
+                    && !err.is_bundle() =>
+            {
+                Ok((entry.value, Freshness::Stale))
+            }
+            _ => Err(err),
+        }
+    }
+
+    async fn get_raw(&self, id: &str) -> Result<(String, Option<u64>), Error> {
+        let meta = self.resolve(id).await?;
+        let result = self.read_raw(&meta).await;
+        self.check_raw_urls(id, result)
+    }
+
+    async fn read_raw(&self, meta: &de::FilesMeta) -> Result<(String, Option<u64>), Error> {
+        Self::check_trashed(meta.trashed_at)?;
+        Self::check_bundle(meta.manifest.as_ref())?;
+        self.check_foreign(meta.foreign.as_deref())?;
+        let expires_at = Self::check_expiry(meta.manifest.as_ref())?;
+        let contents =
+            future::try_join_all(meta.files.iter().map(|file| self.raw(&file.raw_url))).await?;
+        let mut value = String::with_capacity(contents.iter().map(|content| content.len()).sum());
+        for content in &contents {
+            value.push_str(std::str::from_utf8(content).map_err(Error::from_utf8)?);
+        }
+        if let Some(manifest) = meta.manifest.as_ref().filter(|_| self.config.verify) {
+            manifest
+                .check_chunks(meta.files.len())
+                .and_then(|()| manifest.check_len(value.len()))
+                .and_then(|()| manifest.check_sha256(&format::sha256(value.as_bytes())))
+                .map_err(Error::from_integrity)?;
+        }
+        Ok((value, expires_at))
+    }
+
+    pub async fn get_range<R>(&self, id: &str, range: R) -> Result<String, Error>
+    where
+        R: RangeBounds<usize>,
+    {
+        let meta = self.resolve(id).await?;
+        let result = self.read_range(&meta, range).await;
+        self.check_raw_urls(id, result)
+    }
+
+    async fn read_range<R>(&self, meta: &de::FilesMeta, range: R) -> Result<String, Error>
+    where
+        R: RangeBounds<usize>,
+    {
+        Self::check_trashed(meta.trashed_at)?;
+        Self::check_bundle(meta.manifest.as_ref())?;
+        self.check_foreign(meta.foreign.as_deref())?;
+        Self::check_expiry(meta.manifest.as_ref())?;
+        let manifest = meta.manifest.as_ref().filter(|_| self.config.verify);
+        let files = &meta.files;
+        let total_len = files.iter().map(|file| file.size).sum::<usize>();
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start.saturating_add(1),
+            Bound::Unbounded => 0,
+        }
+        .min(total_len);
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end.saturating_add(1),
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => total_len,
+        }
+        .clamp(start, total_len);
+        let mut output = Vec::with_capacity(end - start);
+        let mut file_end = 0;
+        for file in files {
+            let file_start = file_end;
+            file_end += file.size;
+            if file_end <= start {
+                continue;
+            } else if file_start >= end {
+                break;
+            }
+            let content = self.raw(&file.raw_url).await?;
+            if let Some(manifest) = manifest {
+                let content = std::str::from_utf8(&content).map_err(Error::from_utf8)?;
+                manifest
+                    .check_chunk_sha256(file.index, content)
+                    .map_err(Error::from_integrity)?;
+            }
+            let content_end = (end - file_start).min(content.len());
+            let content_start = start.saturating_sub(file_start).min(content_end);
+            output.extend_from_slice(&content[content_start..content_end]);
+        }
+        std::str::from_utf8(format::trim_to_chars(&output))
+            .map(str::to_owned)
+            .map_err(Error::from_utf8)
+    }
+
+    pub fn get_stream(&self, id: &str) -> Chunks<'_> {
+        Chunks::new(&self.client, id, self.config)
+    }
+
+    pub async fn metadata(&self, id: &str) -> Result<Metadata, Error> {
+        let mut metadata = serde_json::from_slice::<de::Metadata>(&self.fetch(id).await?)
+            .map_err(Error::from_json)?;
+        Self::check_trashed(metadata.files.trashed_at)?;
+        Self::check_expiry(metadata.files.manifest.as_ref())?;
+        let manifest = metadata.files.manifest.take();
+        if let Some(manifest) = &manifest {
+            for child in self.fetch_all(&manifest.children).await? {
+                metadata
+                    .files
+                    .append_child(serde_json::from_slice(&child).map_err(Error::from_json)?);
+            }
+        }
+        metadata.files.arrange(manifest.as_ref());
+        Ok(Metadata::new(metadata))
+    }
+
+    pub async fn gist_files(&self, id: &str) -> Result<BTreeMap<String, String>, Error> {
+        let de::GistFiles(files) =
+            serde_json::from_slice(&self.fetch(id).await?).map_err(Error::from_json)?;
+        let mut output = BTreeMap::new();
+        for file in files {
+            let content = if file.truncated {
+                String::from_utf8(self.raw(&file.raw_url).await?.to_vec())
+                    .map_err(|err| Error::from_utf8(err.utf8_error()))?
+            } else {
+                file.content
+            };
+            output.insert(file.filename, content);
+        }
+        Ok(output)
+    }
+
+    pub async fn set(&self, id: &str, value: &str) -> Result<(), Error> {
+        let client = self.client.gist(id).map_err(Error::from_http)?;
+        let current = self.files_len(&client).await?;
+        Self::check_trashed(current.trashed_at())?;
+        Self::check_bundle(current.manifest())?;
+        self.check_foreign(current.foreign())?;
+        let (old_children, expires_at) = match current.manifest() {
+            Some(manifest) => (manifest.children.as_slice(), manifest.expires_at),
+            None => (&[][..], None),
+        };
+        let chunks = ser::chunks(value, self.config.chunking);
+        let digest = ser::Digest::from_chunks(&chunks, self.config.chunk_hashes);
+        let layout = ser::Layout::new(&chunks, digest.names(), current.chunks());
+        let children = self
+            .write_children(&layout.children, current.public())
+            .await?;
+        let manifest = ser::Manifest {
+            children: &children,
+            files: &[],
+            digest: &digest,
+            expires_at,
+        };
+        let result = Self::update(client, unsafe {
+            serde_json::to_vec(
+                &ser::Files::new(&layout.root)
+                    .with_removed(&layout.removed)
+                    .with_manifest(manifest),
             )
-            .map_err(Error::from_http)?
-            .await
-            .map_err(Error::from_hyper)?;
-        if resp.status() == hyper::StatusCode::OK {
+            .unwrap_unchecked()
+        })
+        .await;
+        if let Err(err) = result {
+            self.remove_gists(&children).await.ok();
+            return Err(err);
+        }
+        self.forget(id);
+        self.forget_disk(id).await;
+        self.remove_gists(old_children).await
+    }
+
+    pub async fn set_stream<S, E>(&self, id: &str, stream: S) -> Result<(), Error>
+    where
+        S: Stream<Item = Result<Bytes, E>> + Send + 'static,
+        E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+    {
+        let client = self.client.gist(id).map_err(Error::from_http)?;
+        let current = self.files_len(&client).await?;
+        Self::check_trashed(current.trashed_at())?;
+        Self::check_bundle(current.manifest())?;
+        self.check_foreign(current.foreign())?;
+        let capacity = GIST_MAX_CHUNKS.saturating_sub(current.files_len());
+        let public = current.public();
+        let (current_chunks, manifest) = current.into_parts();
+        let (old_children, expires_at) = match manifest {
+            Some(manifest) => (manifest.children, manifest.expires_at),
+            None => (Vec::new(), None),
+        };
+        let source = Source::new(
+            stream,
+            self.config.chunking,
+            self.config.chunk_hashes,
+            expires_at,
+            current_chunks,
+        );
+        let result = async {
+            if capacity != 0 {
+                Self::update(client, FilesBody::root(source.clone(), capacity)).await?;
+            }
+            let children = self.write_stream_children(&source, public).await?;
+            if let Some(body) = Source::manifest_update(&source, &children) {
+                let client = self.client.gist(id).map_err(Error::from_http)?;
+                if let Err(err) = Self::update(client, body).await {
+                    self.remove_gists(&children).await.ok();
+                    return Err(err);
+                }
+            }
             Ok(())
-        } else {
-            Err(Error::from_status(resp.status()))
         }
+        .await;
+        if let Err(err) = result {
+            self.remove_files(id, &Source::staged(&source)).await.ok();
+            return Err(err);
+        }
+        self.forget(id);
+        self.forget_disk(id).await;
+        self.remove_gists(&old_children).await
+    }
+
+    pub async fn set_reader<R>(&self, id: &str, reader: R) -> Result<(), Error>
+    where
+        R: AsyncRead + Send + 'static,
+    {
+        self.set_stream(id, ReaderStream::new(reader)).await
     }
 
     pub async fn remove(&self, id: &str) -> Result<(), Error> {
-        let resp = self
-            .0
-            .gist(id)
-            .map_err(Error::from_http)?
+        if self.config.soft_delete {
+            self.set_trashed(id, Some(format::unix_time())).await
+        } else {
+            self.delete(id).await
+        }
+    }
+
+    pub async fn restore(&self, id: &str) -> Result<(), Error> {
+        self.set_trashed(id, None).await
+    }
+
+    pub async fn trashed(&self) -> Result<Vec<Trashed>, Error> {
+        let mut trashed = Vec::new();
+        let mut pager = Pager::new(self, None);
+        while let Some(de::Trashed {
+            trashed: entries, ..
+        }) = pager.next().await?
+        {
+            trashed.extend(
+                entries
+                    .into_iter()
+                    .map(|(id, trashed_at)| Trashed { id, trashed_at }),
+            );
+        }
+        Ok(trashed)
+    }
+
+    pub async fn purge(&self, retention: Duration) -> Result<GcReport, Error> {
+        let now = format::unix_time();
+        let trashed = self.trashed().await?;
+        let mut report = GcReport {
+            scanned: trashed.len(),
+            ..GcReport::default()
+        };
+        let mut results = stream::iter(
+            trashed
+                .into_iter()
+                .filter(|trashed| trashed.is_older_than(retention, now)),
+        )
+        .map(|trashed| async move {
+            let result = self.delete(&trashed.id).await;
+            (trashed.id, result)
+        })
+        .buffer_unordered(self.config.gc_concurrency);
+        while let Some((id, result)) = results.next().await {
+            match result {
+                Ok(()) => report.removed.push(id),
+                Err(err) => report.failed.push((id, err)),
+            }
+        }
+        Ok(report)
+    }
+
+    async fn delete(&self, id: &str) -> Result<(), Error> {
+        let client = self.client.gist(id).map_err(Error::from_http)?;
+        let current = self.files_len(&client).await?;
+        self.delete_fetched(id, client, current).await
+    }
+
+    async fn delete_fetched(
+        &self,
+        id: &str,
+        client: http::ClientForUri<'_>,
+        current: de::FilesLen,
+    ) -> Result<(), Error> {
+        self.check_foreign(current.foreign())?;
+        let resp = client
             .into_request(hyper::Method::DELETE, hyper::Body::empty())
             .map_err(Error::from_http)?
             .await
             .map_err(Error::from_hyper)?;
         if resp.status() == hyper::StatusCode::NO_CONTENT {
-            Ok(())
+            self.forget(id);
+            self.forget_disk(id).await;
+            match current.manifest() {
+                Some(manifest) => self.remove_gists(&manifest.children).await,
+                None => Ok(()),
+            }
         } else {
-            Err(Error::from_status(resp.status()))
+            Err(Error::from_headers(resp.status(), resp.headers()))
         }
     }
 
-    fn ids_chunk_future(&self, index: usize) -> Result<IdsChunkFuture<IDS_CHUNK_SIZE>, Error> {
-        self.0
-            .gists_page(IDS_CHUNK_SIZE, index)
-            .request(hyper::Method::GET, hyper::Body::empty())
-            .map(IdsChunkFuture::new)
+    fn ids_chunk_future(
+        &self,
+        username: Option<&str>,
+        index: usize,
+    ) -> Result<IdsChunkFuture<PAGE_SIZE>, Error> {
+        let client = Pager::client(self, username, index)?;
+        let conditional = self
+            .config
+            .etags
+            .then(|| Conditional::new(&self.etags, client.uri().to_string()));
+        client
+            .shared_request(conditional.as_ref().and_then(Conditional::if_none_match))
+            .map(|flight| IdsChunkFuture::new(flight, conditional))
             .map_err(Error::from_http)
     }
 
-    pub fn ids(&self) -> Ids {
-        Ids(match self.ids_chunk_future(1) {
+    fn ids_of(&self, username: Option<&str>) -> Ids<'_> {
+        Ids(match self.ids_chunk_future(username, 1) {
             Ok(fut) => IdsInternal::NotExhausted {
                 stash: self,
+                username: username.map(Box::from),
                 next_index: 2,
                 current_future: fut,
             },
             Err(err) => IdsInternal::Err(err),
         })
     }
-}
 
-const IDS_CHUNK_SIZE: usize = 100;
+    pub async fn rate_limit(&self) -> Result<RateLimit, Error> {
+        let resp = self
+            .client
+            .rate_limit()
+            .shared_request(None)
+            .map_err(Error::from_http)?
+            .await
+            .map_err(Error::from_hyper)?;
+        if resp.status != StatusCode::OK {
+            return Err(Error::from_response(resp.status, resp.retry_after));
+        }
+        serde_json::from_slice(&resp.body)
+            .map(RateLimit::new)
+            .map_err(Error::from_json)
+    }
+
+    pub fn ids(&self) -> Ids<'_> {
+        self.ids_of(None)
+    }
+
+    pub fn user_ids(&self, username: &str) -> Ids<'_> {
+        self.ids_of(Some(username))
+    }
+
+    pub fn listing(&self) -> Listing<'_> {
+        Listing::new(self, None)
+    }
+
+    pub fn user_listing(&self, username: &str) -> Listing<'_> {
+        Listing::new(self, Some(username))
+    }
+
+    pub async fn gc(&self) -> Result<GcReport, Error> {
+        let mut report = GcReport::default();
+        let mut results = self
+            .ids()
+            .map_ok(|chunk| stream::iter(chunk).map(Ok))
+            .try_flatten()
+            .map_ok(|id| async move {
+                let result = self.remove_expired(&id).await;
+                Ok((id, result))
+            })
+            .try_buffer_unordered(self.config.gc_concurrency);
+        while let Some((id, result)) = results.try_next().await? {
+            report.scanned += 1;
+            match result {
+                Ok(true) => report.removed.push(id),
+                Ok(false) => {}
+                Err(err) => report.failed.push((id, err)),
+            }
+        }
+        Ok(report)
+    }
+
+    pub async fn check(&self) -> Result<CheckReport, Error> {
+        self.check_with(false).await
+    }
+
+    pub async fn check_and_repair(&self) -> Result<CheckReport, Error> {
+        self.check_with(true).await
+    }
+
+    async fn check_with(&self, repair: bool) -> Result<CheckReport, Error> {
+        let mut ids = Vec::new();
+        let mut chunks = self.ids();
+        while let Some(chunk) = chunks.next().await {
+            ids.extend(chunk?);
+        }
+        let mut report = CheckReport::default();
+        let mut results = stream::iter(ids)
+            .map(|id| async move {
+                let result = self.check_entry(&id, repair).await;
+                (id, result)
+            })
+            .buffered(self.config.check_concurrency);
+        while let Some((id, result)) = results.next().await {
+            match result {
+                Ok(entry) => report.entries.push(entry),
+                Err(err) => report.failed.push((id, err)),
+            }
+        }
+        Ok(report)
+    }
+
+    pub async fn sync<P>(&self, dir: P, namespace: Option<&str>) -> Result<SyncReport, Error>
+    where
+        P: AsRef<Path>,
+    {
+        self.sync_with(dir.as_ref(), namespace, false).await
+    }
+
+    pub async fn sync_dry_run<P>(
+        &self,
+        dir: P,
+        namespace: Option<&str>,
+    ) -> Result<SyncReport, Error>
+    where
+        P: AsRef<Path>,
+    {
+        self.sync_with(dir.as_ref(), namespace, true).await
+    }
+
+    pub async fn export<W>(&self, mut writer: W) -> Result<usize, Error>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        let mut exported = 0;
+        let mut chunks = self.ids();
+        while let Some(chunk) = chunks.next().await {
+            for id in chunk? {
+                if let Some(line) = self.export_record(&id).await? {
+                    writer
+                        .write_all(&line)
+                        .await
+                        .map_err(|err| Error::from_source(err.into()))?;
+                    exported += 1;
+                }
+            }
+        }
+        writer
+            .flush()
+            .await
+            .map_err(|err| Error::from_source(err.into()))?;
+        Ok(exported)
+    }
+
+    pub async fn import<R>(&self, reader: R) -> ImportReport
+    where
+        R: AsyncRead + Unpin,
+    {
+        let now = format::unix_time();
+        let mut report = ImportReport::default();
+        let mut lines = BufReader::new(reader).lines();
+        let mut line_number = 0;
+        loop {
+            line_number += 1;
+            let line = match lines.next_line().await {
+                Ok(Some(line)) => line,
+                Ok(None) => break,
+                Err(err) => {
+                    report
+                        .failed
+                        .push((line_number, Error::from_source(err.into())));
+                    break;
+                }
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+            let record = match serde_json::from_str::<de::Record>(&line) {
+                Ok(record) => record,
+                Err(err) => {
+                    report.failed.push((line_number, Error::from_json(err)));
+                    continue;
+                }
+            };
+            if record
+                .expires_at
+                .is_some_and(|expires_at| expires_at <= now)
+            {
+                continue;
+            }
+            let visibility = if record.public {
+                Visibility::Public
+            } else {
+                Visibility::Secret
+            };
+            let result = match &record.content {
+                de::RecordContent::Value(value) => {
+                    self.insert_with(value, visibility, record.expires_at).await
+                }
+                de::RecordContent::Files(files) => {
+                    self.insert_files_with(files, visibility, record.expires_at)
+                        .await
+                }
+            };
+            match result {
+                Ok(id) => {
+                    report.imported.insert(record.id, id);
+                }
+                Err(err) => report.failed.push((line_number, err)),
+            }
+        }
+        report
+    }
+
+    pub fn watch(&self, id: &str) -> Watch<'_> {
+        Watch::one(self, id)
+    }
+
+    pub fn watch_all(&self) -> Watch<'_> {
+        Watch::all(self)
+    }
+}
 
 #[repr(transparent)]
-pub struct IdsChunk(std::array::IntoIter<String, IDS_CHUNK_SIZE>);
+pub struct IdsChunk(std::array::IntoIter<String, PAGE_SIZE>);
 
 impl IdsChunk {
     #[inline]
@@ -169,6 +1051,11 @@ impl IdsChunk {
         self.0.len()
     }
 
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.len() == 0
+    }
+
     #[inline]
     pub fn as_slice(&self) -> &[String] {
         self.0.as_slice()
@@ -178,7 +1065,7 @@ impl IdsChunk {
 impl IntoIterator for IdsChunk {
     type Item = String;
 
-    type IntoIter = std::array::IntoIter<String, IDS_CHUNK_SIZE>;
+    type IntoIter = std::array::IntoIter<String, PAGE_SIZE>;
 
     #[inline]
     fn into_iter(self) -> Self::IntoIter {
@@ -191,8 +1078,9 @@ enum IdsInternal<'a> {
     Err(Error),
     NotExhausted {
         stash: &'a Stash,
+        username: Option<Box<str>>,
         next_index: usize,
-        current_future: IdsChunkFuture<IDS_CHUNK_SIZE>,
+        current_future: IdsChunkFuture<PAGE_SIZE>,
     },
 }
 
@@ -211,6 +1099,7 @@ impl<'a> Stream for Ids<'a> {
             },
             IdsInternal::NotExhausted {
                 stash,
+                username,
                 next_index,
                 current_future,
             } => match Future::poll(Pin::new(current_future), cx) {
@@ -219,20 +1108,24 @@ impl<'a> Stream for Ids<'a> {
                     *state = IdsInternal::Exhausted;
                     Poll::Ready(Some(Err(err)))
                 }
-                Poll::Ready(Ok(chunk)) => {
-                    if chunk.len() < IDS_CHUNK_SIZE {
+                Poll::Ready(Ok((chunk, listed_len))) => {
+                    if listed_len < PAGE_SIZE {
                         *state = IdsInternal::Exhausted;
                         if chunk.len() == 0 {
                             return Poll::Ready(None);
                         }
                     } else {
-                        match stash.ids_chunk_future(*next_index) {
+                        match stash.ids_chunk_future(username.as_deref(), *next_index) {
                             Ok(fut) => {
                                 *current_future = fut;
                                 *next_index += 1;
                             }
                             Err(err) => *state = IdsInternal::Err(err),
                         }
+                        if chunk.len() == 0 {
+                            cx.waker().wake_by_ref();
+                            return Poll::Pending;
+                        }
                     }
                     Poll::Ready(Some(Ok(IdsChunk(chunk))))
                 }
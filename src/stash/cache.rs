@@ -0,0 +1,138 @@
+use super::lru::Lru;
+use crate::format;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+enum Bound {
+    Entries(usize),
+    Bytes(usize),
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct Cache {
+    bound: Bound,
+    ttl: Option<Duration>,
+}
+
+impl Cache {
+    pub fn entries(max: usize) -> Self {
+        Self {
+            bound: Bound::Entries(max),
+            ttl: None,
+        }
+    }
+
+    pub fn bytes(max: usize) -> Self {
+        Self {
+            bound: Bound::Bytes(max),
+            ttl: None,
+        }
+    }
+
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub struct CacheStats {
+    hits: u64,
+    misses: u64,
+}
+
+impl CacheStats {
+    #[inline]
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    #[inline]
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+}
+
+struct Entry {
+    value: Arc<str>,
+    expires_at: Option<u64>,
+    inserted: Instant,
+}
+
+pub struct Values {
+    ttl: Option<Duration>,
+    lru: Lru<String, Entry>,
+    generation: u64,
+    stats: CacheStats,
+}
+
+impl Values {
+    pub fn new(config: Cache) -> Self {
+        Self {
+            ttl: config.ttl,
+            lru: match config.bound {
+                Bound::Entries(max) => Lru::new().with_max_entries(max),
+                Bound::Bytes(max) => Lru::new().with_max_bytes(max),
+            },
+            generation: 0,
+            stats: CacheStats::default(),
+        }
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn get(&mut self, id: &str) -> Option<Arc<str>> {
+        let expired = match self.lru.peek(id) {
+            None => {
+                self.stats.misses += 1;
+                return None;
+            }
+            Some(entry) => {
+                self.ttl.is_some_and(|ttl| entry.inserted.elapsed() >= ttl)
+                    || entry
+                        .expires_at
+                        .is_some_and(|expires_at| expires_at <= format::unix_time())
+            }
+        };
+        if expired {
+            self.lru.remove(id);
+            self.stats.misses += 1;
+            return None;
+        }
+        self.stats.hits += 1;
+        self.lru.get(id).map(|entry| entry.value.clone())
+    }
+
+    pub fn insert(
+        &mut self,
+        id: String,
+        value: Arc<str>,
+        expires_at: Option<u64>,
+        generation: u64,
+    ) {
+        if generation != self.generation {
+            return;
+        }
+        let bytes = value.len();
+        let entry = Entry {
+            value,
+            expires_at,
+            inserted: Instant::now(),
+        };
+        self.lru.insert(id, entry, bytes);
+    }
+
+    pub fn remove(&mut self, id: &str) {
+        self.generation += 1;
+        self.lru.remove(id);
+    }
+}
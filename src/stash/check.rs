@@ -0,0 +1,281 @@
+use super::{Error, Stash};
+use crate::{
+    de,
+    format::{self, GIST_MAX_CHUNKS},
+};
+use std::collections::{HashMap, HashSet};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Health {
+    Healthy,
+    Legacy,
+    Corrupt,
+    Foreign,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EntryCheck {
+    id: String,
+    health: Health,
+    reasons: Vec<String>,
+    repaired: Vec<String>,
+}
+
+impl EntryCheck {
+    #[inline]
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    #[inline]
+    pub fn health(&self) -> Health {
+        self.health
+    }
+
+    #[inline]
+    pub fn reasons(&self) -> &[String] {
+        &self.reasons
+    }
+
+    #[inline]
+    pub fn repaired(&self) -> &[String] {
+        &self.repaired
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct CheckReport {
+    pub(super) entries: Vec<EntryCheck>,
+    pub(super) failed: Vec<(String, Error)>,
+}
+
+impl CheckReport {
+    #[inline]
+    pub fn entries(&self) -> &[EntryCheck] {
+        &self.entries
+    }
+
+    #[inline]
+    pub fn failed(&self) -> &[(String, Error)] {
+        &self.failed
+    }
+
+    pub fn count(&self, health: Health) -> usize {
+        self.entries
+            .iter()
+            .filter(|entry| entry.health == health)
+            .count()
+    }
+}
+
+#[derive(Default)]
+struct Scan {
+    chunks: HashMap<String, String>,
+    orphans: Vec<(String, Vec<String>)>,
+    foreign: Vec<String>,
+    corrupt: Vec<String>,
+}
+
+impl Scan {
+    fn files<F>(&mut self, gist: &str, files: Vec<de::GistFile>, expected: F)
+    where
+        F: Fn(&str) -> bool,
+    {
+        let mut orphans = Vec::new();
+        for file in files {
+            if file.filename == format::MANIFEST_FILENAME {
+                continue;
+            }
+            if !format::is_chunk_file(&file.filename) {
+                self.foreign.push(file.filename);
+            } else if !expected(&file.filename) {
+                orphans.push(file.filename);
+            } else if file.truncated {
+                self.corrupt
+                    .push(format!("chunk `{}` is truncated", file.filename));
+            } else {
+                self.chunks.insert(file.filename, file.content);
+            }
+        }
+        if !orphans.is_empty() {
+            self.corrupt.extend(
+                orphans
+                    .iter()
+                    .map(|filename| format!("chunk `{filename}` is orphaned")),
+            );
+            self.orphans.push((gist.to_owned(), orphans));
+        }
+    }
+
+    fn missing(&mut self, names: &[String]) -> bool {
+        let missing = names
+            .iter()
+            .filter(|name| !self.chunks.contains_key(*name))
+            .map(|name| format!("chunk `{name}` is missing"))
+            .collect::<Vec<_>>();
+        let complete = missing.is_empty();
+        self.corrupt.extend(missing);
+        complete
+    }
+}
+
+impl Stash {
+    pub(super) async fn check_entry(&self, id: &str, repair: bool) -> Result<EntryCheck, Error> {
+        let de::GistFiles(files) =
+            serde_json::from_slice(&self.fetch(id).await?).map_err(Error::from_json)?;
+        let manifest = match files
+            .iter()
+            .find(|file| file.filename == format::MANIFEST_FILENAME)
+            .map(|file| serde_json::from_str::<de::Manifest>(&file.content))
+        {
+            None => None,
+            Some(Ok(manifest)) => Some(manifest),
+            Some(Err(err)) => {
+                return Ok(EntryCheck {
+                    id: id.to_owned(),
+                    health: Health::Corrupt,
+                    reasons: vec![format!("manifest is unreadable: {err}")],
+                    repaired: Vec::new(),
+                });
+            }
+        };
+        let mut scan = Scan::default();
+        let mut legacy = Vec::new();
+        match &manifest {
+            None => {
+                scan.files(id, files, |filename| {
+                    format::chunk_index(filename).is_some()
+                });
+                if scan.chunks.is_empty() && scan.corrupt.is_empty() {
+                    scan.foreign.clear();
+                    return Ok(EntryCheck {
+                        id: id.to_owned(),
+                        health: Health::Foreign,
+                        reasons: vec!["no octostash files".to_owned()],
+                        repaired: Vec::new(),
+                    });
+                }
+                legacy.push("manifest is missing".to_owned());
+                let end = scan
+                    .chunks
+                    .keys()
+                    .filter_map(|filename| format::chunk_index(filename))
+                    .max()
+                    .map_or(0, |index| index + 1);
+                scan.missing(&indexed_names(end));
+            }
+            Some(manifest) if !manifest.files.is_empty() => {
+                for file in files {
+                    if file.filename == format::MANIFEST_FILENAME {
+                        continue;
+                    }
+                    if !format::bundle_chunk(&file.filename)
+                        .is_some_and(|(name, _)| manifest.files.iter().any(|file| file == name))
+                    {
+                        scan.foreign.push(file.filename);
+                    } else if file.truncated {
+                        scan.corrupt
+                            .push(format!("file `{}` is truncated", file.filename));
+                    }
+                }
+                match self.get_files(id).await {
+                    Ok(_) => {}
+                    Err(err) if err.is_integrity() => scan.corrupt.push(err.to_string()),
+                    Err(err) if err.is_foreign() => {}
+                    Err(err) => return Err(err),
+                }
+            }
+            Some(manifest) => {
+                if manifest.version == de::LEGACY_VERSION {
+                    legacy.push("manifest version is legacy".to_owned());
+                }
+                let names = manifest
+                    .names()
+                    .map(|names| names.iter().map(String::as_str).collect::<HashSet<_>>());
+                let expected = |filename: &str, first_index: usize| match &names {
+                    Some(names) => names.contains(filename),
+                    None => format::chunk_index(filename).is_some_and(|index| {
+                        (first_index..manifest.chunks.min(first_index + GIST_MAX_CHUNKS))
+                            .contains(&index)
+                    }),
+                };
+                scan.files(id, files, |filename| expected(filename, 0));
+                for (index, child) in manifest.children.iter().enumerate() {
+                    let first_index = (index + 1) * GIST_MAX_CHUNKS;
+                    match self.fetch(child).await {
+                        Ok(body) => {
+                            let de::GistFiles(files) =
+                                serde_json::from_slice(&body).map_err(Error::from_json)?;
+                            scan.files(child, files, |filename| expected(filename, first_index));
+                        }
+                        Err(err) if err.status() == Some(hyper::StatusCode::NOT_FOUND) => {
+                            scan.corrupt
+                                .push(format!("child gist `{child}` is missing"));
+                        }
+                        Err(err) => return Err(err),
+                    }
+                }
+                let names = match manifest.names() {
+                    Some(names) => names.to_vec(),
+                    None => indexed_names(manifest.chunks),
+                };
+                if let Err(reason) = manifest.check_chunks(names.len()) {
+                    scan.corrupt.push(reason.to_owned());
+                }
+                if scan.missing(&names) && scan.corrupt.is_empty() {
+                    let mut value = String::new();
+                    for (index, name) in names.iter().enumerate() {
+                        let chunk = &scan.chunks[name];
+                        if let Err(reason) = manifest.check_chunk_sha256(index, chunk) {
+                            scan.corrupt.push(format!("chunk `{name}`: {reason}"));
+                        }
+                        value.push_str(chunk);
+                    }
+                    if let Err(reason) = manifest
+                        .check_len(value.len())
+                        .and_then(|()| manifest.check_sha256(&format::sha256(value.as_bytes())))
+                    {
+                        scan.corrupt.push(reason.to_owned());
+                    }
+                }
+            }
+        }
+        let mut repaired = Vec::new();
+        if repair && !scan.orphans.is_empty() {
+            for (gist, filenames) in &scan.orphans {
+                self.remove_files(gist, filenames).await?;
+                repaired.extend(filenames.iter().cloned());
+            }
+            self.forget(id);
+            self.forget_disk(id).await;
+        }
+        let health = if !scan.corrupt.is_empty() {
+            Health::Corrupt
+        } else if !scan.foreign.is_empty() {
+            Health::Foreign
+        } else if !legacy.is_empty() {
+            Health::Legacy
+        } else {
+            Health::Healthy
+        };
+        let mut reasons = scan.corrupt;
+        reasons.extend(
+            scan.foreign
+                .iter()
+                .map(|filename| format!("file `{filename}` is foreign")),
+        );
+        reasons.extend(legacy);
+        Ok(EntryCheck {
+            id: id.to_owned(),
+            health,
+            reasons,
+            repaired,
+        })
+    }
+}
+
+fn indexed_names(end: usize) -> Vec<String> {
+    (0..end)
+        .map(|index| format!("{}{index}", format::CHUNK_SEPARATOR))
+        .collect()
+}
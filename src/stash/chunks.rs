@@ -0,0 +1,263 @@
+use super::{
+    futures::{files_meta::FilesMetaFuture, raw::RawFuture},
+    http, Config, Error,
+};
+use crate::{de, ser};
+use futures_core::{Future, Stream};
+use std::{
+    hint::unreachable_unchecked,
+    io, mem,
+    pin::Pin,
+    task::{self, Poll},
+    vec,
+};
+use tokio::io::{AsyncRead, ReadBuf};
+
+struct Verify {
+    manifest: de::Manifest,
+    digest: ser::Digest,
+    chunks: usize,
+}
+
+impl Verify {
+    fn chunk(&mut self, index: usize, chunk: &str) -> Result<(), Error> {
+        self.digest.update(chunk);
+        self.chunks += 1;
+        self.manifest
+            .check_chunk_sha256(index, chunk)
+            .map_err(Error::from_integrity)
+    }
+
+    fn end(&self) -> Result<(), Error> {
+        self.manifest
+            .check_chunks(self.chunks)
+            .and_then(|()| self.manifest.check_len(self.digest.len()))
+            .and_then(|()| self.manifest.check_sha256(&self.digest.sha256()))
+            .map_err(Error::from_integrity)
+    }
+}
+
+enum ChunksInternal<'a> {
+    Exhausted,
+    Err(Error),
+    Meta {
+        client: &'a http::Client,
+        future: FilesMetaFuture,
+        files: Option<de::FilesMeta>,
+        children: vec::IntoIter<String>,
+        manifest: Option<de::Manifest>,
+        config: Config,
+    },
+    Files {
+        client: &'a http::Client,
+        remaining: vec::IntoIter<de::FileMeta>,
+        current_index: usize,
+        current_future: RawFuture,
+        verify: Option<Verify>,
+    },
+}
+
+pub struct Chunks<'a>(ChunksInternal<'a>);
+
+impl<'a> Chunks<'a> {
+    pub(super) fn new(client: &'a http::Client, id: &str, config: Config) -> Self {
+        Self(match Self::meta_future(client, id) {
+            Ok(future) => ChunksInternal::Meta {
+                client,
+                future,
+                files: None,
+                children: Vec::new().into_iter(),
+                manifest: None,
+                config,
+            },
+            Err(err) => ChunksInternal::Err(err),
+        })
+    }
+
+    fn meta_future(client: &'a http::Client, id: &str) -> Result<FilesMetaFuture, Error> {
+        client
+            .gist(id)
+            .and_then(|client| client.into_request(hyper::Method::GET, hyper::Body::empty()))
+            .map(FilesMetaFuture::new)
+            .map_err(Error::from_http)
+    }
+
+    pub fn into_async_read(self) -> ChunksReader<'a> {
+        ChunksReader {
+            chunks: self,
+            chunk: String::new(),
+            pos: 0,
+        }
+    }
+
+    fn next_file(
+        client: &'a http::Client,
+        mut remaining: vec::IntoIter<de::FileMeta>,
+        verify: Option<Verify>,
+    ) -> ChunksInternal<'a> {
+        match remaining.next() {
+            None => match verify.map_or(Ok(()), |verify| verify.end()) {
+                Ok(()) => ChunksInternal::Exhausted,
+                Err(err) => ChunksInternal::Err(err),
+            },
+            Some(file) => match client.raw(&file.raw_url) {
+                Ok(request) => ChunksInternal::Files {
+                    client,
+                    remaining,
+                    current_index: file.index,
+                    current_future: RawFuture::new(request),
+                    verify,
+                },
+                Err(err) => ChunksInternal::Err(Error::from_http(err)),
+            },
+        }
+    }
+}
+
+impl<'a> Stream for Chunks<'a> {
+    type Item = Result<String, Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Option<Self::Item>> {
+        let state = &mut self.get_mut().0;
+        match state {
+            ChunksInternal::Exhausted => Poll::Ready(None),
+            ChunksInternal::Err(_) => match mem::replace(state, ChunksInternal::Exhausted) {
+                ChunksInternal::Err(err) => Poll::Ready(Some(Err(err))),
+                _ => unsafe { unreachable_unchecked() },
+            },
+            ChunksInternal::Meta {
+                client,
+                future,
+                files,
+                children,
+                manifest,
+                config,
+            } => match Future::poll(Pin::new(future), cx) {
+                Poll::Pending => Poll::Pending,
+                Poll::Ready(Err(err)) => {
+                    *state = ChunksInternal::Exhausted;
+                    Poll::Ready(Some(Err(err)))
+                }
+                Poll::Ready(Ok(mut meta)) => {
+                    match files {
+                        Some(files) => files.append_child(meta),
+                        None => {
+                            if meta.trashed_at.is_some() {
+                                *state = ChunksInternal::Err(Error::from_status(
+                                    hyper::StatusCode::NOT_FOUND,
+                                ));
+                                cx.waker().wake_by_ref();
+                                return Poll::Pending;
+                            }
+                            if let Some(mut root_manifest) = meta.manifest.take() {
+                                if root_manifest.is_expired() {
+                                    *state = ChunksInternal::Err(Error::from_status(
+                                        hyper::StatusCode::NOT_FOUND,
+                                    ));
+                                    cx.waker().wake_by_ref();
+                                    return Poll::Pending;
+                                }
+                                if !root_manifest.files.is_empty() {
+                                    *state = ChunksInternal::Err(Error::from_bundle());
+                                    cx.waker().wake_by_ref();
+                                    return Poll::Pending;
+                                }
+                                *children = mem::take(&mut root_manifest.children).into_iter();
+                                *manifest = Some(root_manifest);
+                            }
+                            *files = Some(meta);
+                        }
+                    }
+                    match children.next() {
+                        Some(child) => match Self::meta_future(client, &child) {
+                            Ok(next_future) => *future = next_future,
+                            Err(err) => *state = ChunksInternal::Err(err),
+                        },
+                        None => match files.take() {
+                            Some(de::FilesMeta {
+                                foreign: Some(filename),
+                                ..
+                            }) if config.strict => {
+                                *state = ChunksInternal::Err(Error::from_foreign(&filename));
+                            }
+                            files => {
+                                let files = files
+                                    .map(|mut meta| {
+                                        meta.arrange(manifest.as_ref());
+                                        meta.files
+                                    })
+                                    .unwrap_or_default();
+                                let verify =
+                                    manifest.take().filter(|_| config.verify).map(|manifest| {
+                                        Verify {
+                                            manifest,
+                                            digest: ser::Digest::new(false),
+                                            chunks: 0,
+                                        }
+                                    });
+                                *state = Self::next_file(client, files.into_iter(), verify);
+                            }
+                        },
+                    }
+                    cx.waker().wake_by_ref();
+                    Poll::Pending
+                }
+            },
+            ChunksInternal::Files {
+                client,
+                remaining,
+                current_index,
+                current_future,
+                verify,
+            } => match Future::poll(Pin::new(current_future), cx) {
+                Poll::Pending => Poll::Pending,
+                Poll::Ready(Err(err)) => {
+                    *state = ChunksInternal::Exhausted;
+                    Poll::Ready(Some(Err(err)))
+                }
+                Poll::Ready(Ok(chunk)) => {
+                    if let Some(Err(err)) = verify
+                        .as_mut()
+                        .map(|verify| verify.chunk(*current_index, &chunk))
+                    {
+                        *state = ChunksInternal::Exhausted;
+                        return Poll::Ready(Some(Err(err)));
+                    }
+                    *state = Self::next_file(client, mem::take(remaining), verify.take());
+                    Poll::Ready(Some(Ok(chunk)))
+                }
+            },
+        }
+    }
+}
+
+pub struct ChunksReader<'a> {
+    chunks: Chunks<'a>,
+    chunk: String,
+    pos: usize,
+}
+
+impl<'a> AsyncRead for ChunksReader<'a> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        while this.pos == this.chunk.len() {
+            match Stream::poll_next(Pin::new(&mut this.chunks), cx) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(None) => return Poll::Ready(Ok(())),
+                Poll::Ready(Some(Err(err))) => return Poll::Ready(Err(io::Error::other(err))),
+                Poll::Ready(Some(Ok(chunk))) => {
+                    this.chunk = chunk;
+                    this.pos = 0;
+                }
+            }
+        }
+        let len = buf.remaining().min(this.chunk.len() - this.pos);
+        buf.put_slice(&this.chunk.as_bytes()[this.pos..this.pos + len]);
+        this.pos += len;
+        Poll::Ready(Ok(()))
+    }
+}
@@ -0,0 +1,240 @@
+use crate::format;
+use std::{
+    fmt::{self, Debug},
+    hash::{Hash, Hasher},
+    io,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::SystemTime,
+};
+use tokio::fs;
+
+const INDEX_FILENAME: &str = "index";
+
+const TMP_SUFFIX: &str = ".tmp";
+
+const UNKNOWN_BYTES: u64 = u64::MAX;
+
+static TMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Clone)]
+pub struct DiskCache {
+    dir: PathBuf,
+    max_bytes: Option<u64>,
+    stale_if_error: bool,
+    total_bytes: Arc<AtomicU64>,
+}
+
+impl Debug for DiskCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DiskCache")
+            .field("dir", &self.dir)
+            .field("max_bytes", &self.max_bytes)
+            .field("stale_if_error", &self.stale_if_error)
+            .finish()
+    }
+}
+
+impl PartialEq for DiskCache {
+    fn eq(&self, other: &Self) -> bool {
+        self.dir == other.dir
+            && self.max_bytes == other.max_bytes
+            && self.stale_if_error == other.stale_if_error
+    }
+}
+
+impl Eq for DiskCache {}
+
+impl Hash for DiskCache {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.dir.hash(state);
+        self.max_bytes.hash(state);
+        self.stale_if_error.hash(state);
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Freshness {
+    Fresh,
+    Stale,
+}
+
+pub(super) struct DiskEntry {
+    pub etag: String,
+    pub expires_at: Option<u64>,
+    pub value: String,
+}
+
+impl DiskEntry {
+    pub fn is_expired(&self) -> bool {
+        self.expires_at
+            .is_some_and(|expires_at| expires_at <= format::unix_time())
+    }
+}
+
+impl DiskCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            max_bytes: None,
+            stale_if_error: false,
+            total_bytes: Arc::new(AtomicU64::new(UNKNOWN_BYTES)),
+        }
+    }
+
+    pub fn with_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self
+    }
+
+    pub fn with_stale_if_error(mut self, stale_if_error: bool) -> Self {
+        self.stale_if_error = stale_if_error;
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub(super) fn stale_if_error(&self) -> bool {
+        self.stale_if_error
+    }
+
+    fn entry_dir(&self, id: &str) -> Option<PathBuf> {
+        (!id.is_empty() && id.bytes().all(|byte| byte.is_ascii_alphanumeric()))
+            .then(|| self.dir.join(id))
+    }
+
+    pub(super) async fn read(&self, id: &str) -> Option<DiskEntry> {
+        let entry_dir = self.entry_dir(id)?;
+        let index = fs::read_to_string(entry_dir.join(INDEX_FILENAME))
+            .await
+            .ok()?;
+        let mut lines = index.split('\n');
+        let revision = lines.next()?;
+        let etag = lines.next()?;
+        let expires_at = lines.next().and_then(|expires_at| expires_at.parse().ok());
+        let value = fs::read_to_string(entry_dir.join(revision)).await.ok()?;
+        Some(DiskEntry {
+            etag: etag.to_owned(),
+            expires_at,
+            value,
+        })
+    }
+
+    pub(super) async fn write(
+        &self,
+        id: &str,
+        revision: &str,
+        etag: Option<&str>,
+        expires_at: Option<u64>,
+        value: &str,
+    ) -> io::Result<()> {
+        let Some(entry_dir) = self.entry_dir(id) else {
+            return Ok(());
+        };
+        let revision = format::sha256(revision.as_bytes());
+        fs::create_dir_all(&entry_dir).await?;
+        let old_bytes = dir_bytes(&entry_dir).await;
+        let tmp = entry_dir.join(tmp_filename(&revision));
+        fs::write(&tmp, value).await?;
+        fs::rename(&tmp, entry_dir.join(&revision)).await?;
+        let index_tmp = entry_dir.join(tmp_filename(INDEX_FILENAME));
+        let mut index = format!("{revision}\n{}", etag.unwrap_or_default());
+        if let Some(expires_at) = expires_at {
+            index.push('\n');
+            index.push_str(itoa::Buffer::new().format(expires_at));
+        }
+        let index_len = index.len() as u64;
+        fs::write(&index_tmp, index).await?;
+        fs::rename(&index_tmp, entry_dir.join(INDEX_FILENAME)).await?;
+        let mut entries = fs::read_dir(&entry_dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let filename = entry.file_name();
+            let Some(filename) = filename.to_str() else {
+                continue;
+            };
+            if filename != revision && filename != INDEX_FILENAME && !filename.ends_with(TMP_SUFFIX)
+            {
+                fs::remove_file(entry.path()).await.ok();
+            }
+        }
+        self.adjust_total_bytes(old_bytes, value.len() as u64 + index_len);
+        self.enforce_max_bytes().await
+    }
+
+    pub(super) async fn remove(&self, id: &str) {
+        if let Some(entry_dir) = self.entry_dir(id) {
+            let old_bytes = dir_bytes(&entry_dir).await;
+            if fs::remove_dir_all(entry_dir).await.is_ok() {
+                self.adjust_total_bytes(old_bytes, 0);
+            }
+        }
+    }
+
+    fn adjust_total_bytes(&self, removed: u64, added: u64) {
+        self.total_bytes
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |total| {
+                (total != UNKNOWN_BYTES).then(|| total.saturating_sub(removed) + added)
+            })
+            .ok();
+    }
+
+    async fn enforce_max_bytes(&self) -> io::Result<()> {
+        let Some(max_bytes) = self.max_bytes else {
+            return Ok(());
+        };
+        let total = self.total_bytes.load(Ordering::Relaxed);
+        if total != UNKNOWN_BYTES && total <= max_bytes {
+            return Ok(());
+        }
+        let mut usage = Vec::new();
+        let mut total = 0;
+        let mut entry_dirs = fs::read_dir(&self.dir).await?;
+        while let Some(entry_dir) = entry_dirs.next_entry().await? {
+            let mut bytes = 0;
+            let mut modified = SystemTime::UNIX_EPOCH;
+            let mut entries = fs::read_dir(entry_dir.path()).await?;
+            while let Some(entry) = entries.next_entry().await? {
+                let metadata = entry.metadata().await?;
+                bytes += metadata.len();
+                modified = modified.max(metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH));
+            }
+            total += bytes;
+            usage.push((modified, bytes, entry_dir.path()));
+        }
+        usage.sort_unstable();
+        for (_, bytes, path) in usage {
+            if total <= max_bytes {
+                break;
+            }
+            fs::remove_dir_all(path).await?;
+            total -= bytes;
+        }
+        self.total_bytes.store(total, Ordering::Relaxed);
+        Ok(())
+    }
+}
+
+fn tmp_filename(filename: &str) -> String {
+    format!(
+        "{filename}.{}.{}{TMP_SUFFIX}",
+        std::process::id(),
+        TMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
+async fn dir_bytes(dir: &Path) -> u64 {
+    let mut bytes = 0;
+    if let Ok(mut entries) = fs::read_dir(dir).await {
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            if let Ok(metadata) = entry.metadata().await {
+                bytes += metadata.len();
+            }
+        }
+    }
+    bytes
+}
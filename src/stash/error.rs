@@ -1,11 +1,26 @@
-use hyper::{http, StatusCode};
-use std::fmt::{self, Debug, Display};
+use crate::format;
+use hyper::{http, HeaderMap, StatusCode};
+use std::{
+    error::Error as StdError,
+    fmt::{self, Debug, Display},
+    str::Utf8Error,
+    sync::Arc,
+    time::Duration,
+};
 
 pub enum Internal {
+    Bundle,
     Http(http::Error),
-    Hyper(hyper::Error),
+    Foreign(String),
+    Hyper(Arc<hyper::Error>),
+    Integrity(&'static str),
+    Invalid(&'static str),
     Json(serde_json::Error),
+    NotBundle,
+    RateLimited(StatusCode, Duration),
+    Source(Box<dyn StdError + Send + Sync>),
     Status(StatusCode),
+    Utf8(Utf8Error),
 }
 
 pub struct Error(Internal);
@@ -15,34 +30,114 @@ impl Error {
         Self(Internal::Http(err))
     }
 
-    pub(crate) fn from_hyper(err: hyper::Error) -> Self {
-        Self(Internal::Hyper(err))
+    pub(crate) fn from_bundle() -> Self {
+        Self(Internal::Bundle)
+    }
+
+    pub(crate) fn from_not_bundle() -> Self {
+        Self(Internal::NotBundle)
+    }
+
+    pub(crate) fn from_foreign(filename: &str) -> Self {
+        Self(Internal::Foreign(filename.to_owned()))
+    }
+
+    pub(crate) fn from_hyper(err: impl Into<Arc<hyper::Error>>) -> Self {
+        Self(Internal::Hyper(err.into()))
+    }
+
+    pub(crate) fn from_integrity(reason: &'static str) -> Self {
+        Self(Internal::Integrity(reason))
+    }
+
+    pub(crate) fn from_invalid(reason: &'static str) -> Self {
+        Self(Internal::Invalid(reason))
     }
 
     pub(crate) fn from_json(err: serde_json::Error) -> Self {
         Self(Internal::Json(err))
     }
 
+    pub(crate) fn from_source(err: Box<dyn StdError + Send + Sync>) -> Self {
+        Self(Internal::Source(err))
+    }
+
     pub(crate) fn from_status(err: StatusCode) -> Self {
         Self(Internal::Status(err))
     }
 
+    pub(crate) fn from_response(status: StatusCode, retry_after: Option<Duration>) -> Self {
+        match retry_after {
+            Some(retry_after) => Self(Internal::RateLimited(status, retry_after)),
+            None => Self::from_status(status),
+        }
+    }
+
+    pub(crate) fn from_headers(status: StatusCode, headers: &HeaderMap) -> Self {
+        Self::from_response(status, retry_after(status, headers))
+    }
+
+    pub(crate) fn from_utf8(err: Utf8Error) -> Self {
+        Self(Internal::Utf8(err))
+    }
+
     pub fn status(&self) -> Option<StatusCode> {
-        if let Internal::Status(code) = self.0 {
-            Some(code)
+        match self.0 {
+            Internal::Status(code) | Internal::RateLimited(code, _) => Some(code),
+            _ => None,
+        }
+    }
+
+    pub fn retry_after(&self) -> Option<Duration> {
+        if let Internal::RateLimited(_, retry_after) = self.0 {
+            Some(retry_after)
         } else {
             None
         }
     }
+
+    pub fn is_rate_limited(&self) -> bool {
+        matches!(self.0, Internal::RateLimited(..))
+    }
+
+    pub fn is_bundle(&self) -> bool {
+        matches!(self.0, Internal::Bundle)
+    }
+
+    pub fn is_not_bundle(&self) -> bool {
+        matches!(self.0, Internal::NotBundle)
+    }
+
+    pub fn is_foreign(&self) -> bool {
+        matches!(self.0, Internal::Foreign(_))
+    }
+
+    pub fn is_integrity(&self) -> bool {
+        matches!(self.0, Internal::Integrity(_))
+    }
+
+    pub fn is_invalid(&self) -> bool {
+        matches!(self.0, Internal::Invalid(_))
+    }
 }
 
 impl Debug for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match &self.0 {
+            Internal::Bundle => f.write_str("Bundle"),
+            Internal::Foreign(filename) => write!(f, "Foreign({filename:?})"),
             Internal::Hyper(err) => Debug::fmt(err, f),
             Internal::Http(err) => Debug::fmt(err, f),
+            Internal::Integrity(reason) => write!(f, "Integrity({reason:?})"),
+            Internal::Invalid(reason) => write!(f, "Invalid({reason:?})"),
             Internal::Json(err) => Debug::fmt(err, f),
+            Internal::NotBundle => f.write_str("NotBundle"),
+            Internal::RateLimited(code, retry_after) => {
+                write!(f, "RateLimited({code:?}, {retry_after:?})")
+            }
+            Internal::Source(err) => Debug::fmt(err, f),
             Internal::Status(err) => Debug::fmt(err, f),
+            Internal::Utf8(err) => Debug::fmt(err, f),
         }
     }
 }
@@ -50,21 +145,68 @@ impl Debug for Error {
 impl Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match &self.0 {
+            Internal::Bundle => f.write_str("entry is a file bundle, use get_files"),
+            Internal::Foreign(filename) => {
+                write!(
+                    f,
+                    "gist contains `{filename}`, which octostash did not write"
+                )
+            }
             Internal::Hyper(err) => Display::fmt(err, f),
             Internal::Http(err) => Display::fmt(err, f),
+            Internal::Integrity(reason) => write!(f, "integrity check failed: {reason}"),
+            Internal::Invalid(reason) => write!(f, "invalid input: {reason}"),
             Internal::Json(err) => Display::fmt(err, f),
+            Internal::NotBundle => f.write_str("entry is not a file bundle, use get"),
+            Internal::RateLimited(code, retry_after) => write!(
+                f,
+                "{code}: rate limited, retry after {}s",
+                retry_after.as_secs()
+            ),
+            Internal::Source(err) => Display::fmt(err, f),
             Internal::Status(err) => Display::fmt(err, f),
+            Internal::Utf8(err) => Display::fmt(err, f),
         }
     }
 }
 
-impl std::error::Error for Error {
-    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
         match &self.0 {
+            Internal::Bundle => None,
             Internal::Http(err) => Some(err),
-            Internal::Hyper(err) => Some(err),
+            Internal::Foreign(_) => None,
+            Internal::Hyper(err) => Some(err.as_ref()),
+            Internal::Integrity(_) => None,
+            Internal::Invalid(_) => None,
             Internal::Json(err) => Some(err),
+            Internal::NotBundle => None,
+            Internal::RateLimited(..) => None,
+            Internal::Source(err) => Some(err.as_ref()),
             Internal::Status(_) => None,
+            Internal::Utf8(err) => Some(err),
         }
     }
 }
+
+pub(crate) fn retry_after(status: StatusCode, headers: &HeaderMap) -> Option<Duration> {
+    if status != StatusCode::FORBIDDEN && status != StatusCode::TOO_MANY_REQUESTS {
+        return None;
+    }
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<u64>().ok())
+    };
+    if let Some(seconds) = header("retry-after") {
+        return Some(Duration::from_secs(seconds));
+    }
+    (header("x-ratelimit-remaining") == Some(0)).then(|| {
+        Duration::from_secs(
+            header("x-ratelimit-reset")
+                .unwrap_or_default()
+                .saturating_sub(format::unix_time()),
+        )
+    })
+}
@@ -0,0 +1,65 @@
+use super::lru::Lru;
+use hyper::{body::Bytes, http::HeaderValue};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+const ETAGS_MAX_BYTES: usize = 32 * 1024 * 1024;
+
+#[derive(Clone)]
+pub struct Etags(Arc<Mutex<Lru<String, (HeaderValue, Bytes)>>>);
+
+impl Default for Etags {
+    fn default() -> Self {
+        Self(Arc::new(Mutex::new(
+            Lru::new().with_max_bytes(ETAGS_MAX_BYTES),
+        )))
+    }
+}
+
+impl Etags {
+    fn lock(&self) -> MutexGuard<'_, Lru<String, (HeaderValue, Bytes)>> {
+        self.0.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    pub fn get(&self, key: &str) -> Option<(HeaderValue, Bytes)> {
+        self.lock().get(key).cloned()
+    }
+
+    pub fn insert(&self, key: String, etag: HeaderValue, body: Bytes) {
+        let bytes = body.len();
+        self.lock().insert(key, (etag, body), bytes);
+    }
+
+    pub fn remove(&self, key: &str) {
+        self.lock().remove(key);
+    }
+}
+
+pub struct Conditional {
+    pub etags: Etags,
+    pub key: String,
+    pub cached: Option<(HeaderValue, Bytes)>,
+}
+
+impl Conditional {
+    pub fn new(etags: &Etags, key: String) -> Self {
+        Self {
+            cached: etags.get(&key),
+            etags: etags.clone(),
+            key,
+        }
+    }
+
+    pub fn if_none_match(&self) -> Option<HeaderValue> {
+        self.cached.as_ref().map(|(etag, _)| etag.clone())
+    }
+
+    pub fn not_modified(&mut self) -> Option<Bytes> {
+        self.cached.take().map(|(_, body)| body)
+    }
+
+    pub fn store(self, etag: Option<HeaderValue>, body: Bytes) {
+        if let Some(etag) = etag {
+            self.etags.insert(self.key, etag, body);
+        }
+    }
+}
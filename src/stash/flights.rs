@@ -0,0 +1,160 @@
+use super::{error, futures::body};
+use futures_util::future::{FutureExt as _, Shared, WeakShared};
+use hyper::{body::Bytes, client::ResponseFuture, http::HeaderValue, StatusCode};
+use std::{
+    collections::HashMap,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex, MutexGuard, Weak},
+    time::Duration,
+};
+
+#[derive(Clone)]
+pub struct Response {
+    pub status: StatusCode,
+    pub etag: Option<HeaderValue>,
+    pub retry_after: Option<Duration>,
+    pub body: Bytes,
+}
+
+type Request = Pin<Box<dyn Future<Output = Result<Response, Arc<hyper::Error>>> + Send>>;
+
+pub type Flight = Shared<Request>;
+
+type Key = (String, Option<HeaderValue>);
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub struct FlightStats {
+    requests: u64,
+    coalesced: u64,
+}
+
+impl FlightStats {
+    #[inline]
+    pub fn requests(&self) -> u64 {
+        self.requests
+    }
+
+    #[inline]
+    pub fn coalesced(&self) -> u64 {
+        self.coalesced
+    }
+}
+
+#[derive(Default)]
+struct Inner {
+    next_ticket: u64,
+    coalesced: u64,
+    flights: HashMap<Key, (u64, WeakShared<Request>)>,
+}
+
+#[derive(Clone, Default)]
+pub struct Flights(Arc<Mutex<Inner>>);
+
+impl Flights {
+    fn lock(inner: &Mutex<Inner>) -> MutexGuard<'_, Inner> {
+        inner.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    pub fn join<E>(
+        &self,
+        uri: String,
+        if_none_match: Option<HeaderValue>,
+        request: impl FnOnce() -> Result<ResponseFuture, E>,
+    ) -> Result<Flight, E> {
+        let key = (uri, if_none_match);
+        let mut inner = Self::lock(&self.0);
+        inner
+            .flights
+            .retain(|_, (_, flight)| flight.upgrade().is_some());
+        if let Some(flight) = inner
+            .flights
+            .get(&key)
+            .and_then(|(_, flight)| flight.upgrade())
+        {
+            inner.coalesced += 1;
+            return Ok(flight);
+        }
+        let request = request()?;
+        let ticket = inner.next_ticket;
+        inner.next_ticket += 1;
+        let weak = Arc::downgrade(&self.0);
+        let flight_key = key.clone();
+        let future: Request = Box::pin(async move {
+            let result = Self::complete(request).await;
+            Self::land(&weak, &flight_key, ticket);
+            result
+        });
+        let flight = future.shared();
+        if let Some(waiters) = flight.downgrade() {
+            inner.flights.insert(key, (ticket, waiters));
+        }
+        Ok(flight)
+    }
+
+    pub fn stats(&self) -> FlightStats {
+        let inner = Self::lock(&self.0);
+        FlightStats {
+            requests: inner.next_ticket,
+            coalesced: inner.coalesced,
+        }
+    }
+
+    pub fn forget(&self, uri: &str) {
+        Self::lock(&self.0)
+            .flights
+            .retain(|(flight_uri, _), _| flight_uri != uri);
+    }
+
+    async fn complete(request: ResponseFuture) -> Result<Response, Arc<hyper::Error>> {
+        let resp = request.await?;
+        let status = resp.status();
+        let etag = resp.headers().get(hyper::header::ETAG).cloned();
+        let retry_after = error::retry_after(status, resp.headers());
+        let body = body::SliceFuture::from(resp.into_body()).await?;
+        Ok(Response {
+            status,
+            etag,
+            retry_after,
+            body: body.into(),
+        })
+    }
+
+    fn land(weak: &Weak<Mutex<Inner>>, key: &Key, ticket: u64) {
+        if let Some(inner) = weak.upgrade() {
+            let mut inner = Self::lock(&inner);
+            if matches!(inner.flights.get(key), Some((current, _)) if *current == ticket) {
+                inner.flights.remove(key);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Flights;
+
+    fn request() -> Result<hyper::client::ResponseFuture, ()> {
+        Ok(hyper::Client::new().get(hyper::Uri::from_static("http://127.0.0.1:9/")))
+    }
+
+    #[test]
+    fn coalesces_while_waited_on() {
+        let flights = Flights::default();
+        let first = flights.join("a".to_owned(), None, request).unwrap();
+        let second = flights.join("a".to_owned(), None, request).unwrap();
+        assert_eq!(flights.stats().requests(), 1);
+        assert_eq!(flights.stats().coalesced(), 1);
+        drop((first, second));
+    }
+
+    #[test]
+    fn drops_abandoned_flights() {
+        let flights = Flights::default();
+        drop(flights.join("a".to_owned(), None, request).unwrap());
+        let flight = flights.join("a".to_owned(), None, request).unwrap();
+        assert_eq!(flights.stats().requests(), 2);
+        assert_eq!(flights.stats().coalesced(), 0);
+        drop(flight);
+    }
+}
@@ -1,2 +1,6 @@
 pub mod body;
+pub mod files_body;
+pub mod files_meta;
 pub mod ids_chunk;
+pub mod raw;
+pub mod reader;
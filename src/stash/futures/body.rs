@@ -36,6 +36,22 @@ impl Deref for Slice {
     }
 }
 
+impl From<Bytes> for Slice {
+    fn from(bytes: Bytes) -> Self {
+        Self(SliceInternal::Bytes(bytes))
+    }
+}
+
+impl From<Slice> for Bytes {
+    fn from(slice: Slice) -> Self {
+        match slice.0 {
+            SliceInternal::Empty => Bytes::new(),
+            SliceInternal::Bytes(bytes) => bytes,
+            SliceInternal::Vec(vec) => vec.into(),
+        }
+    }
+}
+
 pub struct SliceFuture {
     body: Body,
     buf: SliceInternal,
@@ -0,0 +1,240 @@
+use crate::{
+    chunking::Chunking,
+    format::{self, GIST_MAX_CHUNKS},
+    ser::{self, Digest, FilesEncoder},
+};
+use futures_core::Stream;
+use hyper::body::Bytes;
+use std::{
+    collections::HashSet,
+    error::Error as StdError,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{self, Poll},
+};
+
+pub struct Source<S> {
+    stream: Pin<Box<S>>,
+    pending: Option<Bytes>,
+    exhausted: bool,
+    digest: Digest,
+    chunking: Chunking,
+    has_manifest: bool,
+    expires_at: Option<u64>,
+    current: Vec<String>,
+    available: HashSet<String>,
+    staged: Vec<String>,
+}
+
+impl<S> Source<S> {
+    pub fn new(
+        stream: S,
+        chunking: Chunking,
+        chunk_hashes: bool,
+        expires_at: Option<u64>,
+        current: Vec<String>,
+    ) -> Arc<Mutex<Self>> {
+        Arc::new(Mutex::new(Self {
+            stream: Box::pin(stream),
+            pending: None,
+            exhausted: false,
+            digest: Digest::new(chunk_hashes),
+            chunking,
+            has_manifest: false,
+            expires_at,
+            available: current.iter().cloned().collect(),
+            current,
+            staged: Vec::new(),
+        }))
+    }
+
+    fn removed(&self) -> Vec<String> {
+        let names = self.digest.names().iter().collect::<HashSet<_>>();
+        self.current
+            .iter()
+            .filter(|name| !names.contains(name))
+            .cloned()
+            .collect()
+    }
+
+    pub fn staged(source: &Mutex<Self>) -> Vec<String> {
+        source
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .staged
+            .clone()
+    }
+
+    fn manifest(&self, children: &[String]) -> String {
+        unsafe {
+            serde_json::to_string(&ser::Manifest {
+                children,
+                files: &[],
+                digest: &self.digest,
+                expires_at: self.expires_at,
+            })
+            .unwrap_unchecked()
+        }
+    }
+
+    pub fn manifest_update(source: &Mutex<Self>, children: &[String]) -> Option<Vec<u8>> {
+        let source = source.lock().unwrap_or_else(|err| err.into_inner());
+        if source.has_manifest && children.is_empty() {
+            return None;
+        }
+        let removed = source.removed();
+        Some(unsafe {
+            serde_json::to_vec(&ser::Files::new(&[]).with_removed(&removed).with_manifest(
+                ser::Manifest {
+                    children,
+                    files: &[],
+                    digest: &source.digest,
+                    expires_at: source.expires_at,
+                },
+            ))
+            .unwrap_unchecked()
+        })
+    }
+}
+
+impl<S, E> Source<S>
+where
+    S: Stream<Item = Result<Bytes, E>>,
+{
+    pub fn poll_has_more(&mut self, cx: &mut task::Context<'_>) -> Poll<Result<bool, E>> {
+        loop {
+            if self.pending.is_some() {
+                return Poll::Ready(Ok(true));
+            } else if self.exhausted {
+                return Poll::Ready(Ok(false));
+            }
+            match self.stream.as_mut().poll_next(cx) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(None) => self.exhausted = true,
+                Poll::Ready(Some(Err(err))) => {
+                    self.exhausted = true;
+                    return Poll::Ready(Err(err));
+                }
+                Poll::Ready(Some(Ok(input))) => {
+                    if !input.is_empty() {
+                        self.pending = Some(input);
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub struct FilesBody<S> {
+    source: Arc<Mutex<Source<S>>>,
+    encoder: Option<FilesEncoder>,
+    is_root: bool,
+}
+
+impl<S> FilesBody<S> {
+    fn chunking(source: &Mutex<Source<S>>) -> Chunking {
+        source
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .chunking
+    }
+
+    pub fn root(source: Arc<Mutex<Source<S>>>, max_files: usize) -> Self {
+        let chunking = Self::chunking(&source);
+        Self {
+            source,
+            encoder: Some(FilesEncoder::new(max_files, chunking)),
+            is_root: true,
+        }
+    }
+
+    pub fn child(source: Arc<Mutex<Source<S>>>, public: bool) -> Self {
+        let chunking = Self::chunking(&source);
+        Self {
+            source,
+            encoder: Some(
+                FilesEncoder::new(GIST_MAX_CHUNKS, chunking)
+                    .with_description(format::CHILD_DESCRIPTION)
+                    .with_public(public),
+            ),
+            is_root: false,
+        }
+    }
+}
+
+impl<S, E> Stream for FilesBody<S>
+where
+    S: Stream<Item = Result<Bytes, E>>,
+    E: Into<Box<dyn StdError + Send + Sync>>,
+{
+    type Item = Result<Bytes, Box<dyn StdError + Send + Sync>>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        let Some(encoder) = &mut this.encoder else {
+            return Poll::Ready(None);
+        };
+        let source = &mut *this.source.lock().unwrap_or_else(|err| err.into_inner());
+        let mut output = Vec::new();
+        loop {
+            let input = match source.pending.take() {
+                Some(input) => input,
+                None if source.exhausted => break,
+                None => match source.stream.as_mut().poll_next(cx) {
+                    Poll::Pending => return Poll::Pending,
+                    Poll::Ready(Some(Ok(input))) => input,
+                    Poll::Ready(Some(Err(err))) => {
+                        source.exhausted = true;
+                        this.encoder = None;
+                        return Poll::Ready(Some(Err(err.into())));
+                    }
+                    Poll::Ready(None) => {
+                        source.exhausted = true;
+                        break;
+                    }
+                },
+            };
+            match encoder.encode(
+                &input,
+                &mut output,
+                &mut source.digest,
+                &mut source.available,
+            ) {
+                Err(err) => {
+                    source.exhausted = true;
+                    this.encoder = None;
+                    return Poll::Ready(Some(Err(err.into())));
+                }
+                Ok(Some(overflow)) => {
+                    source.pending = Some(overflow.into());
+                    break;
+                }
+                Ok(None) if !output.is_empty() => return Poll::Ready(Some(Ok(output.into()))),
+                Ok(None) => {}
+            }
+        }
+        let mut encoder = unsafe { this.encoder.take().unwrap_unchecked() };
+        if let Err(err) = encoder.close(&mut output, &mut source.digest, &mut source.available) {
+            return Poll::Ready(Some(Err(err.into())));
+        }
+        let commit = this.is_root && source.exhausted;
+        let manifest = commit.then(|| source.manifest(&[]));
+        let removed = if commit { source.removed() } else { Vec::new() };
+        source.has_manifest = manifest.is_some();
+        let written = encoder.finish(&removed, manifest.as_deref(), &mut output);
+        if this.is_root && !commit {
+            source.staged = written;
+        }
+        Poll::Ready(Some(Ok(output.into())))
+    }
+}
+
+impl<S, E> From<FilesBody<S>> for hyper::Body
+where
+    S: Stream<Item = Result<Bytes, E>> + Send + 'static,
+    E: Into<Box<dyn StdError + Send + Sync>> + 'static,
+{
+    fn from(body: FilesBody<S>) -> Self {
+        hyper::Body::wrap_stream(body)
+    }
+}
@@ -0,0 +1,52 @@
+use super::{super::Error, body};
+use crate::de;
+use hyper::client::ResponseFuture;
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{self, Poll},
+};
+
+enum FilesMetaFutureInternal {
+    Reqwest(ResponseFuture),
+    Body(body::SliceFuture),
+}
+
+pub struct FilesMetaFuture(FilesMetaFutureInternal);
+
+impl FilesMetaFuture {
+    pub fn new(request: ResponseFuture) -> Self {
+        Self(FilesMetaFutureInternal::Reqwest(request))
+    }
+}
+
+impl Future for FilesMetaFuture {
+    type Output = Result<de::FilesMeta, Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Self::Output> {
+        let state = &mut self.get_mut().0;
+        match state {
+            FilesMetaFutureInternal::Reqwest(request) => {
+                match Future::poll(Pin::new(request), cx) {
+                    Poll::Pending => Poll::Pending,
+                    Poll::Ready(Err(err)) => Poll::Ready(Err(Error::from_hyper(err))),
+                    Poll::Ready(Ok(resp)) if resp.status() != hyper::StatusCode::OK => {
+                        Poll::Ready(Err(Error::from_headers(resp.status(), resp.headers())))
+                    }
+                    Poll::Ready(Ok(resp)) => {
+                        *state = FilesMetaFutureInternal::Body(resp.into_body().into());
+                        cx.waker().wake_by_ref();
+                        Poll::Pending
+                    }
+                }
+            }
+            FilesMetaFutureInternal::Body(fut) => match Future::poll(Pin::new(fut), cx) {
+                Poll::Pending => Poll::Pending,
+                Poll::Ready(Err(err)) => Poll::Ready(Err(Error::from_hyper(err))),
+                Poll::Ready(Ok(slice)) => Poll::Ready(
+                    serde_json::from_slice::<de::FilesMeta>(&slice).map_err(Error::from_json),
+                ),
+            },
+        }
+    }
+}
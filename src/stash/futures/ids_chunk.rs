@@ -1,51 +1,60 @@
-use super::{super::Error, body};
-use hyper::client::ResponseFuture;
+use super::super::{etags::Conditional, flights::Flight, Error};
 use std::{
     future::Future,
     pin::Pin,
     task::{self, Poll},
 };
 
-enum IdsChunkFutureInternal<const CHUNK_SIZE: usize> {
-    Reqwest(ResponseFuture),
-    Body(body::SliceFuture),
+pub struct IdsChunkFuture<const CHUNK_SIZE: usize> {
+    flight: Flight,
+    conditional: Option<Box<Conditional>>,
 }
 
-pub struct IdsChunkFuture<const CHUNK_SIZE: usize>(IdsChunkFutureInternal<CHUNK_SIZE>);
-
 impl<const CHUNK_SIZE: usize> IdsChunkFuture<CHUNK_SIZE> {
-    pub fn new(request: ResponseFuture) -> Self {
-        Self(IdsChunkFutureInternal::Reqwest(request))
+    pub fn new(flight: Flight, conditional: Option<Conditional>) -> Self {
+        Self {
+            flight,
+            conditional: conditional.map(Box::new),
+        }
+    }
+
+    fn parse(slice: &[u8]) -> <Self as Future>::Output {
+        serde_json::from_slice::<crate::de::IdArray<CHUNK_SIZE>>(slice)
+            .map(|ids| {
+                let listed_len = ids.listed_len();
+                (ids.into(), listed_len)
+            })
+            .map_err(Error::from_json)
     }
 }
 
 impl<const CHUNK_SIZE: usize> Future for IdsChunkFuture<CHUNK_SIZE> {
-    type Output = Result<std::array::IntoIter<String, CHUNK_SIZE>, Error>;
+    type Output = Result<(std::array::IntoIter<String, CHUNK_SIZE>, usize), Error>;
 
     fn poll(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Self::Output> {
-        let state = &mut self.get_mut().0;
-        match state {
-            IdsChunkFutureInternal::Reqwest(request) => match Future::poll(Pin::new(request), cx) {
-                Poll::Pending => Poll::Pending,
-                Poll::Ready(Err(err)) => Poll::Ready(Err(Error::from_hyper(err))),
-                Poll::Ready(Ok(resp)) if resp.status() != hyper::StatusCode::OK => {
-                    Poll::Ready(Err(Error::from_status(resp.status())))
+        let this = self.get_mut();
+        match Future::poll(Pin::new(&mut this.flight), cx) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(Err(err)) => Poll::Ready(Err(Error::from_hyper(err))),
+            Poll::Ready(Ok(resp)) if resp.status == hyper::StatusCode::NOT_MODIFIED => {
+                match this
+                    .conditional
+                    .as_mut()
+                    .and_then(|conditional| conditional.not_modified())
+                {
+                    Some(body) => Poll::Ready(Self::parse(&body)),
+                    None => Poll::Ready(Err(Error::from_response(resp.status, resp.retry_after))),
                 }
-                Poll::Ready(Ok(resp)) => {
-                    *state = IdsChunkFutureInternal::Body(resp.into_body().into());
-                    cx.waker().wake_by_ref();
-                    Poll::Pending
+            }
+            Poll::Ready(Ok(resp)) if resp.status != hyper::StatusCode::OK => {
+                Poll::Ready(Err(Error::from_response(resp.status, resp.retry_after)))
+            }
+            Poll::Ready(Ok(resp)) => {
+                if let Some(conditional) = this.conditional.take() {
+                    conditional.store(resp.etag, resp.body.clone());
                 }
-            },
-            IdsChunkFutureInternal::Body(fut) => match Future::poll(Pin::new(fut), cx) {
-                Poll::Pending => Poll::Pending,
-                Poll::Ready(Err(err)) => Poll::Ready(Err(Error::from_hyper(err))),
-                Poll::Ready(Ok(slice)) => Poll::Ready(
-                    serde_json::from_slice::<crate::de::IdArray<CHUNK_SIZE>>(&slice)
-                        .map(Into::into)
-                        .map_err(Error::from_json),
-                ),
-            },
+                Poll::Ready(Self::parse(&resp.body))
+            }
         }
     }
 }
@@ -0,0 +1,51 @@
+use super::{super::Error, body};
+use hyper::client::ResponseFuture;
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{self, Poll},
+};
+
+enum RawFutureInternal {
+    Reqwest(ResponseFuture),
+    Body(body::SliceFuture),
+}
+
+pub struct RawFuture(RawFutureInternal);
+
+impl RawFuture {
+    pub fn new(request: ResponseFuture) -> Self {
+        Self(RawFutureInternal::Reqwest(request))
+    }
+}
+
+impl Future for RawFuture {
+    type Output = Result<String, Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Self::Output> {
+        let state = &mut self.get_mut().0;
+        match state {
+            RawFutureInternal::Reqwest(request) => match Future::poll(Pin::new(request), cx) {
+                Poll::Pending => Poll::Pending,
+                Poll::Ready(Err(err)) => Poll::Ready(Err(Error::from_hyper(err))),
+                Poll::Ready(Ok(resp)) if resp.status() != hyper::StatusCode::OK => {
+                    Poll::Ready(Err(Error::from_headers(resp.status(), resp.headers())))
+                }
+                Poll::Ready(Ok(resp)) => {
+                    *state = RawFutureInternal::Body(resp.into_body().into());
+                    cx.waker().wake_by_ref();
+                    Poll::Pending
+                }
+            },
+            RawFutureInternal::Body(fut) => match Future::poll(Pin::new(fut), cx) {
+                Poll::Pending => Poll::Pending,
+                Poll::Ready(Err(err)) => Poll::Ready(Err(Error::from_hyper(err))),
+                Poll::Ready(Ok(slice)) => Poll::Ready(
+                    std::str::from_utf8(&slice)
+                        .map(String::from)
+                        .map_err(Error::from_utf8),
+                ),
+            },
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use futures_core::Stream;
+use hyper::body::Bytes;
+use std::{
+    io,
+    pin::Pin,
+    task::{self, Poll},
+};
+use tokio::io::{AsyncRead, ReadBuf};
+
+const READ_BUF_LEN: usize = 1024 * 16;
+
+pub struct ReaderStream<R> {
+    reader: Pin<Box<R>>,
+    buf: Box<[u8; READ_BUF_LEN]>,
+}
+
+impl<R> ReaderStream<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader: Box::pin(reader),
+            buf: Box::new([0; READ_BUF_LEN]),
+        }
+    }
+}
+
+impl<R: AsyncRead> Stream for ReaderStream<R> {
+    type Item = io::Result<Bytes>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        let mut buf = ReadBuf::new(&mut this.buf[..]);
+        match this.reader.as_mut().poll_read(cx, &mut buf) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(Err(err)) => Poll::Ready(Some(Err(err))),
+            Poll::Ready(Ok(())) if buf.filled().is_empty() => Poll::Ready(None),
+            Poll::Ready(Ok(())) => Poll::Ready(Some(Ok(Bytes::copy_from_slice(buf.filled())))),
+        }
+    }
+}
@@ -0,0 +1,25 @@
+use super::Error;
+
+#[derive(Debug, Default)]
+pub struct GcReport {
+    pub(super) scanned: usize,
+    pub(super) removed: Vec<String>,
+    pub(super) failed: Vec<(String, Error)>,
+}
+
+impl GcReport {
+    #[inline]
+    pub fn scanned(&self) -> usize {
+        self.scanned
+    }
+
+    #[inline]
+    pub fn removed(&self) -> &[String] {
+        &self.removed
+    }
+
+    #[inline]
+    pub fn failed(&self) -> &[(String, Error)] {
+        &self.failed
+    }
+}
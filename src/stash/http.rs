@@ -7,33 +7,38 @@ use hyper::{
 };
 use hyper_tls::HttpsConnector;
 
+use super::flights::{Flight, FlightStats, Flights};
+use crate::Auth;
+
 pub type Hyper = hyper::Client<HttpsConnector<HttpConnector>>;
 
 #[derive(Clone)]
 pub struct Client {
-    auth: HeaderValue,
+    auth: Option<Auth>,
     hyper: Hyper,
+    flights: Flights,
 }
 
 impl Client {
-    pub fn new(auth: HeaderValue) -> Self {
+    pub fn new(auth: Option<Auth>) -> Self {
         let mut https = HttpsConnector::new();
         https.https_only(true);
         Self {
             auth,
             hyper: hyper::Client::builder().http2_only(true).build(https),
+            flights: Flights::default(),
         }
     }
 
-    pub fn auth(&self) -> &HeaderValue {
-        &self.auth
+    pub fn auth(&self) -> Option<&Auth> {
+        self.auth.as_ref()
     }
 
     pub fn hyper(&self) -> &Hyper {
         &self.hyper
     }
 
-    pub fn gists(&self) -> ClientForUri {
+    pub fn gists(&self) -> ClientForUri<'_> {
         ClientForUri {
             client: self,
             uri: unsafe {
@@ -47,7 +52,21 @@ impl Client {
         }
     }
 
-    pub fn gists_page(&self, per_page: usize, page: usize) -> ClientForUri {
+    pub fn rate_limit(&self) -> ClientForUri<'_> {
+        ClientForUri {
+            client: self,
+            uri: unsafe {
+                Uri::builder()
+                    .scheme(uri::Scheme::HTTPS)
+                    .authority("api.github.com")
+                    .path_and_query("/rate_limit")
+                    .build()
+                    .unwrap_unchecked()
+            },
+        }
+    }
+
+    pub fn gists_page(&self, per_page: usize, page: usize) -> ClientForUri<'_> {
         ClientForUri {
             client: self,
             uri: unsafe {
@@ -61,7 +80,23 @@ impl Client {
         }
     }
 
-    pub fn gist(&self, id: &str) -> Result<ClientForUri, http::Error> {
+    pub fn user_gists_page(
+        &self,
+        username: &str,
+        per_page: usize,
+        page: usize,
+    ) -> Result<ClientForUri<'_>, http::Error> {
+        Uri::builder()
+            .scheme(uri::Scheme::HTTPS)
+            .authority("api.github.com")
+            .path_and_query(format!(
+                "/users/{username}/gists?per_page={per_page}&page={page}"
+            ))
+            .build()
+            .map(|uri| ClientForUri { client: self, uri })
+    }
+
+    pub fn gist(&self, id: &str) -> Result<ClientForUri<'_>, http::Error> {
         Uri::builder()
             .scheme(uri::Scheme::HTTPS)
             .authority("api.github.com")
@@ -69,17 +104,44 @@ impl Client {
             .build()
             .map(|uri| ClientForUri { client: self, uri })
     }
+
+    pub fn raw(&self, raw_url: &str) -> Result<hyper::client::ResponseFuture, http::Error> {
+        hyper::Request::builder()
+            .uri(raw_url)
+            .header(header::USER_AGENT, unsafe {
+                HeaderValue::from_maybe_shared_unchecked(Bytes::from_static(b"octostash"))
+            })
+            .method(hyper::Method::GET)
+            .body(hyper::Body::empty())
+            .map(|request| self.hyper.request(request))
+    }
+
+    pub fn shared_raw(&self, raw_url: &str) -> Result<Flight, http::Error> {
+        self.flights
+            .join(raw_url.to_owned(), None, || self.raw(raw_url))
+    }
+
+    pub fn flight_stats(&self) -> FlightStats {
+        self.flights.stats()
+    }
+
+    pub fn forget(&self, uri: &str) {
+        self.flights.forget(uri);
+    }
 }
 
 fn append_headers(
-    request_builder: http::request::Builder,
-    auth: HeaderValue,
+    mut request_builder: http::request::Builder,
+    auth: Option<&Auth>,
 ) -> http::request::Builder {
+    if let Some(auth) = auth {
+        request_builder =
+            request_builder.header(header::AUTHORIZATION, auth.header_value().clone());
+    }
     request_builder
         .header(header::USER_AGENT, unsafe {
             HeaderValue::from_maybe_shared_unchecked(Bytes::from_static(b"octostash"))
         })
-        .header(header::AUTHORIZATION, auth)
         .header(header::ACCEPT, unsafe {
             HeaderValue::from_maybe_shared_unchecked(Bytes::from_static(
                 b"application/vnd.github+json",
@@ -97,18 +159,36 @@ pub struct ClientForUri<'a> {
 }
 
 impl<'a> ClientForUri<'a> {
-    pub fn request(
+    pub fn uri(&self) -> &Uri {
+        &self.uri
+    }
+
+    pub fn conditional_request(
         &self,
-        method: hyper::Method,
-        body: hyper::Body,
+        if_none_match: Option<HeaderValue>,
     ) -> Result<hyper::client::ResponseFuture, http::Error> {
-        append_headers(
+        let mut request_builder = append_headers(
             hyper::Request::builder().uri(&self.uri),
-            self.client.auth.clone(),
-        )
-        .method(method)
-        .body(body)
-        .map(|request| self.client.hyper.request(request))
+            self.client.auth.as_ref(),
+        );
+        if let Some(etag) = if_none_match {
+            request_builder = request_builder.header(header::IF_NONE_MATCH, etag);
+        }
+        request_builder
+            .method(hyper::Method::GET)
+            .body(hyper::Body::empty())
+            .map(|request| self.client.hyper.request(request))
+    }
+
+    pub fn shared_request(
+        &self,
+        if_none_match: Option<HeaderValue>,
+    ) -> Result<Flight, http::Error> {
+        self.client
+            .flights
+            .join(self.uri.to_string(), if_none_match.clone(), || {
+                self.conditional_request(if_none_match)
+            })
     }
 
     pub fn into_request(
@@ -118,7 +198,7 @@ impl<'a> ClientForUri<'a> {
     ) -> Result<hyper::client::ResponseFuture, http::Error> {
         append_headers(
             hyper::Request::builder().uri(self.uri),
-            self.client.auth.clone(),
+            self.client.auth.as_ref(),
         )
         .method(method)
         .body(body)
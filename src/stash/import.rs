@@ -0,0 +1,20 @@
+use super::Error;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Default)]
+pub struct ImportReport {
+    pub(super) imported: BTreeMap<String, String>,
+    pub(super) failed: Vec<(usize, Error)>,
+}
+
+impl ImportReport {
+    #[inline]
+    pub fn imported(&self) -> &BTreeMap<String, String> {
+        &self.imported
+    }
+
+    #[inline]
+    pub fn failed(&self) -> &[(usize, Error)] {
+        &self.failed
+    }
+}
@@ -0,0 +1,102 @@
+use super::{pager::Pager, Error, Stash, Visibility};
+use crate::de;
+use futures_core::Stream;
+use futures_util::stream;
+use std::{
+    collections::VecDeque,
+    fmt::{self, Debug},
+    pin::Pin,
+    task::{self, Poll},
+};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Listed {
+    id: String,
+    visibility: Visibility,
+    html_url: String,
+    created_at: String,
+    updated_at: String,
+}
+
+impl Listed {
+    fn new(gist: de::ListedGist) -> Self {
+        Self {
+            id: gist.id,
+            visibility: if gist.public {
+                Visibility::Public
+            } else {
+                Visibility::Secret
+            },
+            html_url: gist.html_url,
+            created_at: gist.created_at,
+            updated_at: gist.updated_at,
+        }
+    }
+
+    #[inline]
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    #[inline]
+    pub fn visibility(&self) -> Visibility {
+        self.visibility
+    }
+
+    #[inline]
+    pub fn html_url(&self) -> &str {
+        &self.html_url
+    }
+
+    #[inline]
+    pub fn created_at(&self) -> &str {
+        &self.created_at
+    }
+
+    #[inline]
+    pub fn updated_at(&self) -> &str {
+        &self.updated_at
+    }
+}
+
+pub struct Listing<'a>(Pin<Box<dyn Stream<Item = Result<Listed, Error>> + Send + 'a>>);
+
+impl Debug for Listing<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Listing").finish_non_exhaustive()
+    }
+}
+
+impl Stream for Listing<'_> {
+    type Item = Result<Listed, Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut().0.as_mut().poll_next(cx)
+    }
+}
+
+struct Pages<'a> {
+    pager: Pager<'a>,
+    gists: VecDeque<de::ListedGist>,
+}
+
+impl<'a> Listing<'a> {
+    pub(super) fn new(stash: &'a Stash, username: Option<&str>) -> Self {
+        let state = Pages {
+            pager: Pager::new(stash, username),
+            gists: VecDeque::new(),
+        };
+        Self(Box::pin(stream::unfold(state, |mut state| async move {
+            loop {
+                if let Some(gist) = state.gists.pop_front() {
+                    return Some((Ok(Listed::new(gist)), state));
+                }
+                match state.pager.next::<de::Listing>().await {
+                    Ok(None) => return None,
+                    Ok(Some(listing)) => state.gists.extend(listing.gists),
+                    Err(err) => return Some((Err(err), state)),
+                }
+            }
+        })))
+    }
+}
@@ -0,0 +1,183 @@
+use std::{
+    borrow::Borrow,
+    collections::{BTreeMap, HashMap},
+    hash::Hash,
+};
+
+struct Entry<V> {
+    value: V,
+    bytes: usize,
+    tick: u64,
+}
+
+pub struct Lru<K, V> {
+    entries: HashMap<K, Entry<V>>,
+    order: BTreeMap<u64, K>,
+    tick: u64,
+    bytes: usize,
+    max_entries: usize,
+    max_bytes: usize,
+}
+
+impl<K, V> Lru<K, V>
+where
+    K: Eq + Hash + Clone,
+{
+    pub fn new() -> Self {
+        Self {
+            entries: HashMap::new(),
+            order: BTreeMap::new(),
+            tick: 0,
+            bytes: 0,
+            max_entries: usize::MAX,
+            max_bytes: usize::MAX,
+        }
+    }
+
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries;
+        self
+    }
+
+    pub fn with_max_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    #[cfg(test)]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[cfg(test)]
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    pub fn peek<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.entries.get(key).map(|entry| &entry.value)
+    }
+
+    pub fn get<Q>(&mut self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        let entry = self.entries.get_mut(key)?;
+        self.tick += 1;
+        let key = unsafe { self.order.remove(&entry.tick).unwrap_unchecked() };
+        self.order.insert(self.tick, key);
+        entry.tick = self.tick;
+        Some(&entry.value)
+    }
+
+    pub fn insert(&mut self, key: K, value: V, bytes: usize) -> bool {
+        if bytes > self.max_bytes {
+            return false;
+        }
+        self.remove(&key);
+        self.tick += 1;
+        self.bytes += bytes;
+        self.order.insert(self.tick, key.clone());
+        self.entries.insert(
+            key,
+            Entry {
+                value,
+                bytes,
+                tick: self.tick,
+            },
+        );
+        while self.entries.len() > self.max_entries || self.bytes > self.max_bytes {
+            let Some((_, evicted)) = self.order.pop_first() else {
+                break;
+            };
+            if let Some(entry) = self.entries.remove(&evicted) {
+                self.bytes -= entry.bytes;
+            }
+        }
+        true
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        let entry = self.entries.remove(key)?;
+        self.order.remove(&entry.tick);
+        self.bytes -= entry.bytes;
+        Some(entry.value)
+    }
+}
+
+impl<K, V> Default for Lru<K, V>
+where
+    K: Eq + Hash + Clone,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Lru;
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let mut lru = Lru::new().with_max_entries(2);
+        lru.insert("a", 1, 0);
+        lru.insert("b", 2, 0);
+        assert_eq!(lru.get("a"), Some(&1));
+        lru.insert("c", 3, 0);
+        assert_eq!(lru.len(), 2);
+        assert_eq!(lru.peek("a"), Some(&1));
+        assert_eq!(lru.peek("b"), None);
+        assert_eq!(lru.peek("c"), Some(&3));
+    }
+
+    #[test]
+    fn peek_does_not_refresh() {
+        let mut lru = Lru::new().with_max_entries(2);
+        lru.insert("a", 1, 0);
+        lru.insert("b", 2, 0);
+        assert_eq!(lru.peek("a"), Some(&1));
+        lru.insert("c", 3, 0);
+        assert_eq!(lru.peek("a"), None);
+        assert_eq!(lru.peek("b"), Some(&2));
+    }
+
+    #[test]
+    fn bounds_bytes() {
+        let mut lru = Lru::new().with_max_bytes(10);
+        assert!(lru.insert("a", 'a', 4));
+        assert!(lru.insert("b", 'b', 4));
+        assert_eq!(lru.bytes(), 8);
+        assert!(lru.insert("c", 'c', 4));
+        assert_eq!(lru.bytes(), 8);
+        assert_eq!(lru.peek("a"), None);
+        assert!(!lru.insert("d", 'd', 11));
+        assert_eq!(lru.peek("d"), None);
+        assert_eq!(lru.len(), 2);
+        assert!(lru.insert("e", 'e', 10));
+        assert_eq!(lru.len(), 1);
+        assert_eq!(lru.bytes(), 10);
+    }
+
+    #[test]
+    fn replaces_and_removes() {
+        let mut lru = Lru::new().with_max_bytes(10);
+        lru.insert("a", 1, 6);
+        lru.insert("a", 2, 3);
+        assert_eq!(lru.bytes(), 3);
+        assert_eq!(lru.get("a"), Some(&2));
+        assert_eq!(lru.remove("a"), Some(2));
+        assert_eq!(lru.remove("a"), None);
+        assert_eq!(lru.bytes(), 0);
+        assert_eq!(lru.len(), 0);
+    }
+}
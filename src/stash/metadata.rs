@@ -0,0 +1,70 @@
+use crate::de;
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub enum Visibility {
+    Public,
+    #[default]
+    Secret,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Metadata {
+    id: String,
+    visibility: Visibility,
+    html_url: String,
+    created_at: String,
+    updated_at: String,
+    raw_urls: Vec<String>,
+}
+
+impl Metadata {
+    pub(super) fn new(metadata: de::Metadata) -> Self {
+        Self {
+            id: metadata.id,
+            visibility: if metadata.public {
+                Visibility::Public
+            } else {
+                Visibility::Secret
+            },
+            html_url: metadata.html_url,
+            created_at: metadata.created_at,
+            updated_at: metadata.updated_at,
+            raw_urls: metadata
+                .files
+                .files
+                .into_iter()
+                .map(|file| file.raw_url)
+                .collect(),
+        }
+    }
+
+    #[inline]
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    #[inline]
+    pub fn visibility(&self) -> Visibility {
+        self.visibility
+    }
+
+    #[inline]
+    pub fn html_url(&self) -> &str {
+        &self.html_url
+    }
+
+    #[inline]
+    pub fn created_at(&self) -> &str {
+        &self.created_at
+    }
+
+    #[inline]
+    pub fn updated_at(&self) -> &str {
+        &self.updated_at
+    }
+
+    #[inline]
+    pub fn raw_urls(&self) -> &[String] {
+        &self.raw_urls
+    }
+}
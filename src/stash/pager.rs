@@ -0,0 +1,119 @@
+use super::{http, Error, Stash};
+use crate::de;
+use hyper::header::HeaderValue;
+use serde::de::DeserializeOwned;
+
+pub(super) const PAGE_SIZE: usize = 100;
+
+pub(super) struct Pager<'a> {
+    stash: &'a Stash,
+    username: Option<String>,
+    pages: usize,
+    exhausted: bool,
+}
+
+impl<'a> Pager<'a> {
+    pub fn new(stash: &'a Stash, username: Option<&str>) -> Self {
+        Self {
+            stash,
+            username: username.map(str::to_owned),
+            pages: 0,
+            exhausted: false,
+        }
+    }
+
+    pub fn client(
+        stash: &'a Stash,
+        username: Option<&str>,
+        page: usize,
+    ) -> Result<http::ClientForUri<'a>, Error> {
+        match username {
+            None => Ok(stash.client.gists_page(PAGE_SIZE, page)),
+            Some(username) => {
+                Stash::check_username(username)?;
+                stash
+                    .client
+                    .user_gists_page(username, PAGE_SIZE, page)
+                    .map_err(Error::from_http)
+            }
+        }
+    }
+
+    pub fn pages(&self) -> usize {
+        self.pages
+    }
+
+    pub fn is_exhausted(&self) -> bool {
+        self.exhausted
+    }
+
+    fn next_client(&mut self) -> Result<http::ClientForUri<'a>, Error> {
+        self.pages += 1;
+        let client = Self::client(self.stash, self.username.as_deref(), self.pages);
+        self.exhausted = client.is_err();
+        client
+    }
+
+    fn end_page(&mut self, listed_len: usize) {
+        self.exhausted = listed_len < PAGE_SIZE;
+    }
+
+    pub async fn next<T>(&mut self) -> Result<Option<T>, Error>
+    where
+        T: DeserializeOwned + de::Page,
+    {
+        if self.exhausted {
+            return Ok(None);
+        }
+        let client = self.next_client()?;
+        let result = self
+            .stash
+            .conditional_get(&client)
+            .await
+            .and_then(|body| serde_json::from_slice::<T>(&body).map_err(Error::from_json));
+        match result {
+            Ok(page) => {
+                self.end_page(page.listed_len());
+                Ok(Some(page))
+            }
+            Err(err) => {
+                self.exhausted = true;
+                Err(err)
+            }
+        }
+    }
+
+    pub async fn revalidate<T>(
+        &mut self,
+        cached: Option<(HeaderValue, usize)>,
+    ) -> Result<Option<(T, Option<HeaderValue>)>, Error>
+    where
+        T: DeserializeOwned + de::Page,
+    {
+        let client = self.next_client()?;
+        let etag = cached.as_ref().map(|(etag, _)| etag.clone());
+        let result = match Stash::revalidate(&client, etag).await {
+            Ok(None) => match cached {
+                Some((_, listed_len)) => {
+                    self.end_page(listed_len);
+                    return Ok(None);
+                }
+                None => Err(Error::from_status(hyper::StatusCode::NOT_MODIFIED)),
+            },
+            Ok(Some((body, etag))) => serde_json::from_slice::<T>(&body)
+                .map(|page| (page, etag))
+                .map_err(Error::from_json),
+            Err(err) => Err(err),
+        };
+        match result {
+            Ok((page, etag)) => {
+                self.end_page(page.listed_len());
+                Ok(Some((page, etag)))
+            }
+            Err(err) => {
+                self.exhausted = true;
+                Err(err)
+            }
+        }
+    }
+}
@@ -0,0 +1,453 @@
+use super::{
+    cache,
+    etags::Conditional,
+    futures::{
+        body,
+        files_body::{FilesBody, Source},
+    },
+    http, Error, Stash,
+};
+use crate::{de, format, ser};
+use futures_core::Stream;
+use futures_util::{future, stream, StreamExt as _, TryStreamExt as _};
+use hyper::{body::Bytes, header::HeaderValue};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+impl Stash {
+    pub(super) async fn create(&self, body: impl Into<hyper::Body>) -> Result<String, Error> {
+        let resp = self
+            .client
+            .gists()
+            .into_request(hyper::Method::POST, body.into())
+            .map_err(Error::from_http)?
+            .await
+            .map_err(Error::from_hyper)?;
+        if resp.status() == hyper::StatusCode::CREATED {
+            serde_json::from_slice::<de::Id>(
+                &body::SliceFuture::from(resp.into_body())
+                    .await
+                    .map_err(Error::from_hyper)?,
+            )
+            .map(String::from)
+            .map_err(Error::from_json)
+        } else {
+            Err(Error::from_headers(resp.status(), resp.headers()))
+        }
+    }
+
+    pub(super) async fn update(
+        client: http::ClientForUri<'_>,
+        body: impl Into<hyper::Body>,
+    ) -> Result<(), Error> {
+        let resp = client
+            .into_request(hyper::Method::PATCH, body.into())
+            .map_err(Error::from_http)?
+            .await
+            .map_err(Error::from_hyper)?;
+        if resp.status() == hyper::StatusCode::OK {
+            Ok(())
+        } else {
+            Err(Error::from_headers(resp.status(), resp.headers()))
+        }
+    }
+
+    pub(super) async fn conditional_get(
+        &self,
+        client: &http::ClientForUri<'_>,
+    ) -> Result<body::Slice, Error> {
+        let mut conditional = self
+            .config
+            .etags
+            .then(|| Conditional::new(&self.etags, client.uri().to_string()));
+        let resp = client
+            .shared_request(conditional.as_ref().and_then(Conditional::if_none_match))
+            .map_err(Error::from_http)?
+            .await
+            .map_err(Error::from_hyper)?;
+        match resp.status {
+            hyper::StatusCode::NOT_MODIFIED => {
+                match conditional.as_mut().and_then(Conditional::not_modified) {
+                    Some(body) => Ok(body.into()),
+                    None => Err(Error::from_status(hyper::StatusCode::NOT_MODIFIED)),
+                }
+            }
+            hyper::StatusCode::OK => {
+                if let Some(conditional) = conditional {
+                    conditional.store(resp.etag, resp.body.clone());
+                }
+                Ok(resp.body.into())
+            }
+            status => Err(Error::from_response(status, resp.retry_after)),
+        }
+    }
+
+    pub(super) async fn revalidate(
+        client: &http::ClientForUri<'_>,
+        etag: Option<HeaderValue>,
+    ) -> Result<Option<(body::Slice, Option<HeaderValue>)>, Error> {
+        let resp = client
+            .shared_request(etag)
+            .map_err(Error::from_http)?
+            .await
+            .map_err(Error::from_hyper)?;
+        match resp.status {
+            hyper::StatusCode::NOT_MODIFIED => Ok(None),
+            hyper::StatusCode::OK => Ok(Some((resp.body.into(), resp.etag))),
+            status => Err(Error::from_response(status, resp.retry_after)),
+        }
+    }
+
+    pub(super) fn check_trashed(trashed_at: Option<u64>) -> Result<(), Error> {
+        match trashed_at {
+            Some(_) => Err(Error::from_status(hyper::StatusCode::NOT_FOUND)),
+            None => Ok(()),
+        }
+    }
+
+    pub(super) fn check_username(username: &str) -> Result<(), Error> {
+        if username.is_empty() || username.len() > 39 {
+            Err(Error::from_invalid("user name must be 1 to 39 characters"))
+        } else if !username
+            .bytes()
+            .all(|byte| byte.is_ascii_alphanumeric() || byte == b'-')
+        {
+            Err(Error::from_invalid(
+                "user name may only contain alphanumerics and `-`",
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    pub(super) fn check_bundle(manifest: Option<&de::Manifest>) -> Result<(), Error> {
+        match manifest {
+            Some(manifest) if !manifest.files.is_empty() => Err(Error::from_bundle()),
+            _ => Ok(()),
+        }
+    }
+
+    pub(super) async fn set_trashed(&self, id: &str, trashed_at: Option<u64>) -> Result<(), Error> {
+        let client = self.client.gist(id).map_err(Error::from_http)?;
+        let current = self.files_len(&client).await?;
+        self.check_foreign(current.foreign())?;
+        if current.trashed_at().is_some() == trashed_at.is_some() {
+            return Ok(());
+        }
+        let description = match trashed_at {
+            Some(trashed_at) => format::trash_description(trashed_at, current.description()),
+            None => format::restored_description(current.description()).to_owned(),
+        };
+        Self::update(client, unsafe {
+            serde_json::to_vec(&ser::Files::new(&[]).with_description(&description))
+                .unwrap_unchecked()
+        })
+        .await?;
+        self.forget(id);
+        self.forget_disk(id).await;
+        Ok(())
+    }
+
+    pub(super) fn check_expiry(manifest: Option<&de::Manifest>) -> Result<Option<u64>, Error> {
+        match manifest {
+            Some(manifest) if manifest.is_expired() => {
+                Err(Error::from_status(hyper::StatusCode::NOT_FOUND))
+            }
+            manifest => Ok(manifest.and_then(|manifest| manifest.expires_at)),
+        }
+    }
+
+    pub(super) async fn export_record(&self, id: &str) -> Result<Option<Vec<u8>>, Error> {
+        let root = self.fetch(id).await?;
+        let metadata = serde_json::from_slice::<de::Metadata>(&root).map_err(Error::from_json)?;
+        let manifest = metadata.files.manifest.as_ref();
+        if manifest.is_none() && metadata.files.files.is_empty()
+            || manifest.is_some_and(de::Manifest::is_expired)
+        {
+            return Ok(None);
+        }
+        let expires_at = manifest.and_then(|manifest| manifest.expires_at);
+        let mut line = match manifest.filter(|manifest| !manifest.files.is_empty()) {
+            Some(_) => {
+                let files = self.bundle_files(&root)?;
+                serde_json::to_vec(&ser::Record {
+                    id,
+                    public: metadata.public,
+                    created_at: &metadata.created_at,
+                    updated_at: &metadata.updated_at,
+                    expires_at,
+                    content: ser::RecordContent::Files(&files),
+                })
+            }
+            None => {
+                let (value, _) = self.assemble(&root).await?;
+                serde_json::to_vec(&ser::Record {
+                    id,
+                    public: metadata.public,
+                    created_at: &metadata.created_at,
+                    updated_at: &metadata.updated_at,
+                    expires_at,
+                    content: ser::RecordContent::Value(&value),
+                })
+            }
+        }
+        .map_err(Error::from_json)?;
+        line.push(b'\n');
+        Ok(Some(line))
+    }
+
+    pub(super) async fn remove_expired(&self, id: &str) -> Result<bool, Error> {
+        let client = self.client.gist(id).map_err(Error::from_http)?;
+        let current = self.files_len(&client).await?;
+        if current.manifest().is_some_and(de::Manifest::is_expired) {
+            self.delete_fetched(id, client, current)
+                .await
+                .map(|()| true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    pub(super) async fn assemble(&self, root: &[u8]) -> Result<(String, Option<u64>), Error> {
+        let mut files = serde_json::from_slice::<de::Files>(root).map_err(Error::from_json)?;
+        Self::check_trashed(files.trashed_at())?;
+        let children = self.fetch_all(files.children()).await?;
+        for child in &children {
+            files.append_child(serde_json::from_slice(child).map_err(Error::from_json)?);
+        }
+        let manifest = files.take_manifest();
+        Self::check_bundle(manifest.as_ref())?;
+        self.check_foreign(files.foreign())?;
+        let expires_at = Self::check_expiry(manifest.as_ref())?;
+        let (value, chunks_len) = files
+            .into_value(manifest.as_ref())
+            .map_err(Error::from_integrity)?;
+        if let Some(manifest) = manifest.filter(|_| self.config.verify) {
+            manifest
+                .check_chunks(chunks_len)
+                .and_then(|()| manifest.check_len(value.len()))
+                .and_then(|()| manifest.check_sha256(&format::sha256(value.as_bytes())))
+                .map_err(Error::from_integrity)?;
+        }
+        Ok((value, expires_at))
+    }
+
+    pub(super) async fn files_len(
+        &self,
+        client: &http::ClientForUri<'_>,
+    ) -> Result<de::FilesLen, Error> {
+        serde_json::from_slice::<de::FilesLen>(&self.conditional_get(client).await?)
+            .map_err(Error::from_json)
+    }
+
+    pub(super) fn check_foreign(&self, foreign: Option<&str>) -> Result<(), Error> {
+        match foreign {
+            Some(filename) if self.config.strict => Err(Error::from_foreign(filename)),
+            _ => Ok(()),
+        }
+    }
+
+    pub(super) async fn fetch(&self, id: &str) -> Result<body::Slice, Error> {
+        self.conditional_get(&self.client.gist(id).map_err(Error::from_http)?)
+            .await
+    }
+
+    pub(super) async fn resolve(&self, id: &str) -> Result<Arc<de::FilesMeta>, Error> {
+        if self.config.raw_reads {
+            if let Some(meta) = self.raw_urls.get(id, self.config.raw_reads_ttl) {
+                return Ok(meta);
+            }
+        }
+        let mut meta = serde_json::from_slice::<de::FilesMeta>(&self.fetch(id).await?)
+            .map_err(Error::from_json)?;
+        let manifest = meta.manifest.take();
+        if let Some(manifest) = &manifest {
+            for child in self.fetch_all(&manifest.children).await? {
+                meta.append_child(serde_json::from_slice(&child).map_err(Error::from_json)?);
+            }
+        }
+        meta.arrange(manifest.as_ref());
+        meta.manifest = manifest;
+        let meta = Arc::new(meta);
+        if self.config.raw_reads && !self.config.raw_reads_ttl.is_zero() {
+            self.raw_urls.insert(id.to_owned(), meta.clone());
+        }
+        Ok(meta)
+    }
+
+    pub(super) fn check_raw_urls<T>(&self, id: &str, result: Result<T, Error>) -> Result<T, Error> {
+        if let Err(err) = &result {
+            if err.is_integrity() || err.status() == Some(hyper::StatusCode::NOT_FOUND) {
+                self.raw_urls.remove(id);
+            }
+        }
+        result
+    }
+
+    pub(super) fn forget(&self, id: &str) {
+        self.raw_urls.remove(id);
+        if let Some(mut cache) = self.cache() {
+            cache.remove(id);
+        }
+        if let Ok(client) = self.client.gist(id) {
+            let uri = client.uri().to_string();
+            self.client.forget(&uri);
+            self.etags.remove(&uri);
+        }
+    }
+
+    pub(super) async fn forget_disk(&self, id: &str) {
+        if let Some(disk_cache) = &self.disk_cache {
+            disk_cache.remove(id).await;
+        }
+    }
+
+    pub(super) fn cache(&self) -> Option<MutexGuard<'_, cache::Values>> {
+        self.cache
+            .as_ref()
+            .map(|cache| cache.lock().unwrap_or_else(|err| err.into_inner()))
+    }
+
+    pub(super) async fn fetch_all(&self, ids: &[String]) -> Result<Vec<body::Slice>, Error> {
+        let fetches: Vec<_> = ids.iter().map(|id| self.fetch(id)).collect();
+        stream::iter(fetches)
+            .buffered(self.config.transfer_concurrency)
+            .try_collect()
+            .await
+    }
+
+    pub(super) async fn raw(&self, raw_url: &str) -> Result<body::Slice, Error> {
+        let resp = self
+            .client
+            .shared_raw(raw_url)
+            .map_err(Error::from_http)?
+            .await
+            .map_err(Error::from_hyper)?;
+        if resp.status == hyper::StatusCode::OK {
+            Ok(resp.body.into())
+        } else {
+            Err(Error::from_response(resp.status, resp.retry_after))
+        }
+    }
+
+    pub(super) async fn write_children(
+        &self,
+        children: &[Vec<(&str, &str)>],
+        public: bool,
+    ) -> Result<Vec<String>, Error> {
+        let uploads: Vec<_> = children
+            .iter()
+            .enumerate()
+            .map(|(index, chunks)| {
+                let body = unsafe {
+                    serde_json::to_vec(
+                        &ser::Files::new(chunks)
+                            .with_public(public)
+                            .with_description(format::CHILD_DESCRIPTION),
+                    )
+                    .unwrap_unchecked()
+                };
+                self.write_child(index, body)
+            })
+            .collect();
+        let mut results: Vec<_> = stream::iter(uploads)
+            .buffer_unordered(self.config.transfer_concurrency)
+            .collect()
+            .await;
+        results.sort_unstable_by_key(|(index, _)| *index);
+        let mut children = Vec::with_capacity(results.len());
+        let mut error = None;
+        for (_, result) in results {
+            match result {
+                Ok(child) => children.push(child),
+                Err(err) => {
+                    error.get_or_insert(err);
+                }
+            }
+        }
+        match error {
+            None => Ok(children),
+            Some(err) => {
+                self.remove_gists(&children).await.ok();
+                Err(err)
+            }
+        }
+    }
+
+    async fn write_child(&self, index: usize, body: Vec<u8>) -> (usize, Result<String, Error>) {
+        (index, self.create(body).await)
+    }
+
+    pub(super) async fn write_stream_children<S, E>(
+        &self,
+        source: &Arc<Mutex<Source<S>>>,
+        public: bool,
+    ) -> Result<Vec<String>, Error>
+    where
+        S: Stream<Item = Result<Bytes, E>> + Send + 'static,
+        E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+    {
+        let mut children = Vec::new();
+        loop {
+            let result = match Self::has_more(source).await {
+                Ok(false) => return Ok(children),
+                Ok(true) => self.create(FilesBody::child(source.clone(), public)).await,
+                Err(err) => Err(err),
+            };
+            match result {
+                Ok(child) => children.push(child),
+                Err(err) => {
+                    self.remove_gists(&children).await.ok();
+                    return Err(err);
+                }
+            }
+        }
+    }
+
+    pub(super) async fn remove_files(&self, id: &str, filenames: &[String]) -> Result<(), Error> {
+        if filenames.is_empty() {
+            return Ok(());
+        }
+        Self::update(self.client.gist(id).map_err(Error::from_http)?, unsafe {
+            serde_json::to_vec(&ser::Removal::new(filenames)).unwrap_unchecked()
+        })
+        .await
+    }
+
+    async fn remove_gist(&self, id: &str) -> Result<(), Error> {
+        let resp = self
+            .client
+            .gist(id)
+            .map_err(Error::from_http)?
+            .into_request(hyper::Method::DELETE, hyper::Body::empty())
+            .map_err(Error::from_http)?
+            .await
+            .map_err(Error::from_hyper)?;
+        match resp.status() {
+            hyper::StatusCode::NO_CONTENT | hyper::StatusCode::NOT_FOUND => Ok(()),
+            status => Err(Error::from_headers(status, resp.headers())),
+        }
+    }
+
+    pub(super) async fn remove_gists(&self, ids: &[String]) -> Result<(), Error> {
+        let removals: Vec<_> = ids.iter().map(|id| self.remove_gist(id)).collect();
+        stream::iter(removals)
+            .buffer_unordered(self.config.transfer_concurrency)
+            .try_collect()
+            .await
+    }
+
+    pub(super) async fn has_more<S, E>(source: &Mutex<Source<S>>) -> Result<bool, Error>
+    where
+        S: Stream<Item = Result<Bytes, E>>,
+        E: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        future::poll_fn(|cx| {
+            source
+                .lock()
+                .unwrap_or_else(|err| err.into_inner())
+                .poll_has_more(cx)
+        })
+        .await
+        .map_err(|err| Error::from_source(err.into()))
+    }
+}
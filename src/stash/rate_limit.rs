@@ -0,0 +1,41 @@
+use crate::de;
+use std::time::{Duration, SystemTime};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct RateLimit {
+    limit: u64,
+    remaining: u64,
+    used: u64,
+    reset: u64,
+}
+
+impl RateLimit {
+    pub(super) fn new(rate_limit: de::RateLimit) -> Self {
+        Self {
+            limit: rate_limit.limit,
+            remaining: rate_limit.remaining,
+            used: rate_limit.used,
+            reset: rate_limit.reset,
+        }
+    }
+
+    #[inline]
+    pub fn limit(&self) -> u64 {
+        self.limit
+    }
+
+    #[inline]
+    pub fn remaining(&self) -> u64 {
+        self.remaining
+    }
+
+    #[inline]
+    pub fn used(&self) -> u64 {
+        self.used
+    }
+
+    #[inline]
+    pub fn reset(&self) -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_secs(self.reset)
+    }
+}
@@ -0,0 +1,51 @@
+use super::lru::Lru;
+use crate::de;
+use std::{
+    sync::{Arc, Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+const RAW_URLS_CAPACITY: usize = 1024;
+
+struct Entry {
+    meta: Arc<de::FilesMeta>,
+    inserted: Instant,
+}
+
+#[derive(Clone)]
+pub struct RawUrls(Arc<Mutex<Lru<String, Entry>>>);
+
+impl Default for RawUrls {
+    fn default() -> Self {
+        Self(Arc::new(Mutex::new(
+            Lru::new().with_max_entries(RAW_URLS_CAPACITY),
+        )))
+    }
+}
+
+impl RawUrls {
+    fn lock(&self) -> MutexGuard<'_, Lru<String, Entry>> {
+        self.0.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    pub fn get(&self, id: &str, ttl: Duration) -> Option<Arc<de::FilesMeta>> {
+        let mut lru = self.lock();
+        if lru.peek(id)?.inserted.elapsed() >= ttl {
+            lru.remove(id);
+            return None;
+        }
+        lru.get(id).map(|entry| entry.meta.clone())
+    }
+
+    pub fn insert(&self, id: String, meta: Arc<de::FilesMeta>) {
+        let entry = Entry {
+            meta,
+            inserted: Instant::now(),
+        };
+        self.lock().insert(id, entry, 0);
+    }
+
+    pub fn remove(&self, id: &str) {
+        self.lock().remove(id);
+    }
+}
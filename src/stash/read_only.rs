@@ -0,0 +1,124 @@
+use super::{
+    http, Cache, CacheStats, Chunks, DiskCache, Error, FlightStats, Freshness, Ids, Listing,
+    Metadata, RateLimit, Stash, Watch,
+};
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Debug},
+    ops::RangeBounds,
+    time::Duration,
+};
+
+#[derive(Clone)]
+#[repr(transparent)]
+pub struct ReadOnlyStash(Stash);
+
+impl Debug for ReadOnlyStash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReadOnlyStash")
+            .field("hyper", &self.0.client.hyper())
+            .field("verify", &self.0.config.verify)
+            .field("strict", &self.0.config.strict)
+            .field("raw_reads", &self.0.config.raw_reads)
+            .field("etags", &self.0.config.etags)
+            .finish()
+    }
+}
+
+impl Default for ReadOnlyStash {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ReadOnlyStash {
+    pub fn new() -> Self {
+        Self(Stash::with_client(http::Client::new(None)))
+    }
+
+    pub fn with_verify(self, verify: bool) -> Self {
+        Self(self.0.with_verify(verify))
+    }
+
+    pub fn with_strict(self, strict: bool) -> Self {
+        Self(self.0.with_strict(strict))
+    }
+
+    pub fn with_raw_reads(self, raw_reads: bool) -> Self {
+        Self(self.0.with_raw_reads(raw_reads))
+    }
+
+    pub fn with_etags(self, etags: bool) -> Self {
+        Self(self.0.with_etags(etags))
+    }
+
+    pub fn with_watch_interval(self, watch_interval: Duration) -> Self {
+        Self(self.0.with_watch_interval(watch_interval))
+    }
+
+    pub fn with_cache(self, cache: Cache) -> Self {
+        Self(self.0.with_cache(cache))
+    }
+
+    pub fn with_disk_cache(self, disk_cache: DiskCache) -> Self {
+        Self(self.0.with_disk_cache(disk_cache))
+    }
+
+    pub fn cache_stats(&self) -> Option<CacheStats> {
+        self.0.cache_stats()
+    }
+
+    pub fn flight_stats(&self) -> FlightStats {
+        self.0.flight_stats()
+    }
+
+    pub async fn get(&self, id: &str) -> Result<String, Error> {
+        self.0.get(id).await
+    }
+
+    pub async fn get_with_freshness(&self, id: &str) -> Result<(String, Freshness), Error> {
+        self.0.get_with_freshness(id).await
+    }
+
+    pub async fn get_range<R>(&self, id: &str, range: R) -> Result<String, Error>
+    where
+        R: RangeBounds<usize>,
+    {
+        self.0.get_range(id, range).await
+    }
+
+    pub fn get_stream(&self, id: &str) -> Chunks<'_> {
+        self.0.get_stream(id)
+    }
+
+    pub async fn get_files(&self, id: &str) -> Result<BTreeMap<String, String>, Error> {
+        self.0.get_files(id).await
+    }
+
+    pub async fn metadata(&self, id: &str) -> Result<Metadata, Error> {
+        self.0.metadata(id).await
+    }
+
+    pub async fn rate_limit(&self) -> Result<RateLimit, Error> {
+        self.0.rate_limit().await
+    }
+
+    pub fn user_ids(&self, username: &str) -> Ids<'_> {
+        self.0.user_ids(username)
+    }
+
+    pub fn user_listing(&self, username: &str) -> Listing<'_> {
+        self.0.user_listing(username)
+    }
+
+    pub fn watch(&self, id: &str) -> Watch<'_> {
+        self.0.watch(id)
+    }
+}
+
+impl From<Stash> for ReadOnlyStash {
+    #[inline]
+    fn from(stash: Stash) -> Self {
+        Self(stash)
+    }
+}
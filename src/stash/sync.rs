@@ -0,0 +1,607 @@
+use super::{futures::body, Error, Stash, StatusCode};
+use crate::{de, format, ser};
+use futures_util::{stream, StreamExt as _};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io,
+    path::{Path, PathBuf},
+};
+
+pub(super) const STATE_FILENAME: &str = ".octostash-sync.json";
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum SyncAction {
+    Upload,
+    Download,
+    DeleteRemote,
+    DeleteLocal,
+    Conflict,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SyncChange {
+    key: String,
+    action: SyncAction,
+}
+
+impl SyncChange {
+    #[inline]
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    #[inline]
+    pub fn action(&self) -> SyncAction {
+        self.action
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct SyncReport {
+    namespace: Option<String>,
+    dry_run: bool,
+    changes: Vec<SyncChange>,
+    failed: Vec<(String, Error)>,
+}
+
+impl SyncReport {
+    #[inline]
+    pub fn namespace(&self) -> Option<&str> {
+        self.namespace.as_deref()
+    }
+
+    #[inline]
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    #[inline]
+    pub fn changes(&self) -> &[SyncChange] {
+        &self.changes
+    }
+
+    #[inline]
+    pub fn failed(&self) -> &[(String, Error)] {
+        &self.failed
+    }
+
+    pub fn count(&self, action: SyncAction) -> usize {
+        self.changes
+            .iter()
+            .filter(|change| change.action == action)
+            .count()
+    }
+}
+
+struct Remote {
+    id: String,
+    revision: String,
+    root: body::Slice,
+}
+
+enum Plan {
+    Keep,
+    Change(SyncAction),
+    Compare(de::SyncEntry),
+    Record(de::SyncEntry),
+    Forget,
+}
+
+fn io_error(err: io::Error) -> Error {
+    Error::from_source(err.into())
+}
+
+fn key_path(dir: &Path, key: &str) -> Result<PathBuf, Error> {
+    let mut path = dir.to_owned();
+    for segment in key.split('/') {
+        if segment.is_empty() || segment == "." || segment == ".." || segment.contains('\\') {
+            return Err(Error::from_invalid("sync key is not a relative path"));
+        }
+        path.push(segment);
+    }
+    if key == STATE_FILENAME {
+        return Err(Error::from_invalid("sync key names the state file"));
+    }
+    Ok(path)
+}
+
+fn plan(base: Option<&de::SyncEntry>, sha256: Option<String>, remote: Option<&Remote>) -> Plan {
+    let local_changed = match (base, &sha256) {
+        (Some(base), Some(sha256)) => base.sha256 != *sha256,
+        (None, None) => false,
+        _ => true,
+    };
+    let remote_changed = match (base, remote) {
+        (Some(base), Some(remote)) => base.id != remote.id || base.revision != remote.revision,
+        (None, None) => false,
+        _ => true,
+    };
+    match (local_changed, remote_changed, sha256, remote) {
+        (false, false, _, _) => Plan::Keep,
+        (true, false, Some(_), _) => Plan::Change(SyncAction::Upload),
+        (true, false, None, Some(_)) => Plan::Change(SyncAction::DeleteRemote),
+        (false, true, _, Some(_)) => Plan::Change(SyncAction::Download),
+        (false, true, Some(_), None) => Plan::Change(SyncAction::DeleteLocal),
+        (_, _, None, None) => Plan::Forget,
+        (true, true, Some(sha256), Some(remote)) => Plan::Compare(de::SyncEntry {
+            id: remote.id.clone(),
+            sha256,
+            revision: remote.revision.clone(),
+        }),
+        _ => Plan::Change(SyncAction::Conflict),
+    }
+}
+
+async fn read_state(dir: &Path) -> Result<de::SyncState, Error> {
+    match tokio::fs::read(dir.join(STATE_FILENAME)).await {
+        Ok(state) => serde_json::from_slice(&state).map_err(Error::from_json),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(de::SyncState::default()),
+        Err(err) => Err(io_error(err)),
+    }
+}
+
+async fn write_state(dir: &Path, namespace: &str, state: &de::SyncState) -> Result<(), Error> {
+    let entries = state
+        .entries
+        .iter()
+        .map(|(key, entry)| {
+            (
+                key.as_str(),
+                ser::SyncEntry {
+                    id: &entry.id,
+                    sha256: &entry.sha256,
+                    revision: &entry.revision,
+                },
+            )
+        })
+        .collect::<Vec<_>>();
+    let state = serde_json::to_vec(&ser::SyncState {
+        namespace,
+        entries: &entries,
+    })
+    .map_err(Error::from_json)?;
+    let path = dir.join(STATE_FILENAME);
+    let tmp = dir.join(format!("{STATE_FILENAME}.tmp"));
+    tokio::fs::write(&tmp, state).await.map_err(io_error)?;
+    tokio::fs::rename(&tmp, &path).await.map_err(io_error)
+}
+
+async fn scan_local(
+    dir: &Path,
+    failed: &mut Vec<(String, Error)>,
+) -> Result<BTreeMap<String, String>, Error> {
+    let mut files = BTreeMap::new();
+    let mut dirs = vec![(dir.to_owned(), String::new())];
+    while let Some((path, prefix)) = dirs.pop() {
+        let mut entries = tokio::fs::read_dir(&path).await.map_err(io_error)?;
+        while let Some(entry) = entries.next_entry().await.map_err(io_error)? {
+            let name = entry.file_name();
+            let key = format!("{prefix}{}", name.to_string_lossy());
+            let Some(name) = name.to_str() else {
+                failed.push((key, Error::from_invalid("file name is not utf-8")));
+                continue;
+            };
+            if prefix.is_empty()
+                && (name == STATE_FILENAME || name == format!("{STATE_FILENAME}.tmp"))
+            {
+                continue;
+            }
+            let file_type = entry.file_type().await.map_err(io_error)?;
+            if file_type.is_dir() {
+                dirs.push((entry.path(), format!("{key}/")));
+            } else if file_type.is_file() {
+                match tokio::fs::read(entry.path()).await {
+                    Ok(content) => match String::from_utf8(content) {
+                        Ok(content) => {
+                            files.insert(key, content);
+                        }
+                        Err(err) => failed.push((key, Error::from_utf8(err.utf8_error()))),
+                    },
+                    Err(err) => failed.push((key, io_error(err))),
+                }
+            } else if file_type.is_symlink() {
+                failed.push((key, Error::from_invalid("symlinks are not synced")));
+            } else {
+                failed.push((key, Error::from_invalid("not a regular file")));
+            }
+        }
+    }
+    Ok(files)
+}
+
+impl Stash {
+    pub(super) async fn sync_with(
+        &self,
+        dir: &Path,
+        namespace: Option<&str>,
+        dry_run: bool,
+    ) -> Result<SyncReport, Error> {
+        let mut state = read_state(dir).await?;
+        let namespace = match (namespace, state.namespace.take()) {
+            (Some(namespace), Some(synced)) if namespace != synced => {
+                return Err(Error::from_invalid(
+                    "directory is synced with another namespace",
+                ))
+            }
+            (Some(namespace), _) => Some(namespace.to_owned()),
+            (None, synced) => synced,
+        };
+        let mut report = SyncReport {
+            dry_run,
+            ..SyncReport::default()
+        };
+        let index = match &namespace {
+            Some(namespace) => self.sync_index(namespace).await?,
+            None => BTreeMap::new(),
+        };
+        let local = scan_local(dir, &mut report.failed).await?;
+        if namespace.is_none() && local.is_empty() && state.entries.is_empty() {
+            return Ok(report);
+        }
+        let mut remote = BTreeMap::new();
+        let mut stale = BTreeSet::new();
+        let mut results = stream::iter(&index)
+            .map(|(key, id)| async move { (key, self.sync_remote(id).await) })
+            .buffered(self.config.transfer_concurrency);
+        while let Some((key, result)) = results.next().await {
+            match result {
+                Ok(Some(entry)) => {
+                    remote.insert(key.clone(), entry);
+                }
+                Ok(None) => {
+                    stale.insert(key.clone());
+                }
+                Err(err) => report.failed.push((key.clone(), err)),
+            }
+        }
+        let skipped = report
+            .failed
+            .iter()
+            .map(|(key, _)| key.clone())
+            .collect::<BTreeSet<_>>();
+        let keys = local
+            .keys()
+            .chain(index.keys())
+            .chain(state.entries.keys())
+            .filter(|key| !skipped.contains(*key))
+            .cloned()
+            .collect::<BTreeSet<_>>();
+        let mut added = BTreeMap::new();
+        let mut removed = stale;
+        for key in keys {
+            let local = local.get(&key);
+            let remote = remote.get(&key);
+            let plan = match self.sync_plan(state.entries.get(&key), local, remote).await {
+                Ok(plan) => plan,
+                Err(err) => {
+                    report.failed.push((key, err));
+                    continue;
+                }
+            };
+            let action = match plan {
+                Plan::Keep => continue,
+                Plan::Change(action) => action,
+                Plan::Compare(_) => SyncAction::Conflict,
+                Plan::Record(entry) => {
+                    if !dry_run {
+                        state.entries.insert(key, entry);
+                    }
+                    continue;
+                }
+                Plan::Forget => {
+                    if !dry_run {
+                        state.entries.remove(&key);
+                    }
+                    continue;
+                }
+            };
+            if dry_run || action == SyncAction::Conflict {
+                report.changes.push(SyncChange { key, action });
+                continue;
+            }
+            let result = match action {
+                SyncAction::Upload => {
+                    let content = local.map_or("", String::as_str);
+                    self.sync_upload(remote.map(|remote| remote.id.as_str()), content)
+                        .await
+                        .map(|entry| {
+                            if remote.is_none() {
+                                added.insert(key.clone(), entry.id.clone());
+                            }
+                            state.entries.insert(key.clone(), entry);
+                        })
+                }
+                SyncAction::Download => match (key_path(dir, &key), remote) {
+                    (Ok(path), Some(remote)) => {
+                        self.sync_download(&path, remote).await.map(|entry| {
+                            state.entries.insert(key.clone(), entry);
+                        })
+                    }
+                    (Err(err), _) => Err(err),
+                    (_, None) => Err(Error::from_status(StatusCode::NOT_FOUND)),
+                },
+                SyncAction::DeleteRemote => match remote {
+                    Some(remote) => match self.remove(&remote.id).await {
+                        Err(err) if err.status() != Some(StatusCode::NOT_FOUND) => Err(err),
+                        _ => {
+                            removed.insert(key.clone());
+                            state.entries.remove(&key);
+                            Ok(())
+                        }
+                    },
+                    None => Ok(()),
+                },
+                SyncAction::DeleteLocal => match key_path(dir, &key) {
+                    Ok(path) => match tokio::fs::remove_file(&path).await {
+                        Err(err) if err.kind() != io::ErrorKind::NotFound => Err(io_error(err)),
+                        _ => {
+                            state.entries.remove(&key);
+                            Ok(())
+                        }
+                    },
+                    Err(err) => Err(err),
+                },
+                SyncAction::Conflict => Ok(()),
+            };
+            match result {
+                Ok(()) => report.changes.push(SyncChange { key, action }),
+                Err(err) => report.failed.push((key, err)),
+            }
+        }
+        if dry_run {
+            report.namespace = namespace;
+            return Ok(report);
+        }
+        if namespace.is_none() && added.is_empty() {
+            return Ok(report);
+        }
+        let namespace = match self
+            .sync_commit(namespace.as_deref(), &added, &removed)
+            .await
+        {
+            Ok(namespace) => namespace,
+            Err(err) => {
+                for (key, id) in &added {
+                    self.remove(id).await.ok();
+                    state.entries.remove(key);
+                }
+                if let Some(namespace) = &namespace {
+                    write_state(dir, namespace, &state).await.ok();
+                }
+                return Err(err);
+            }
+        };
+        write_state(dir, &namespace, &state).await?;
+        report.namespace = Some(namespace);
+        Ok(report)
+    }
+
+    async fn sync_commit(
+        &self,
+        namespace: Option<&str>,
+        added: &BTreeMap<String, String>,
+        removed: &BTreeSet<String>,
+    ) -> Result<String, Error> {
+        match namespace {
+            Some(namespace) if added.is_empty() && removed.is_empty() => Ok(namespace.to_owned()),
+            Some(namespace) => {
+                let mut index = self.sync_index(namespace).await?;
+                index.retain(|key, _| !removed.contains(key));
+                index.extend(added.clone());
+                self.set(
+                    namespace,
+                    &serde_json::to_string(&index).map_err(Error::from_json)?,
+                )
+                .await?;
+                Ok(namespace.to_owned())
+            }
+            None => {
+                self.insert(&serde_json::to_string(added).map_err(Error::from_json)?)
+                    .await
+            }
+        }
+    }
+
+    async fn sync_index(&self, namespace: &str) -> Result<BTreeMap<String, String>, Error> {
+        let root = self.fetch(namespace).await?;
+        let (index, _) = self.assemble(&root).await?;
+        serde_json::from_str(&index).map_err(Error::from_json)
+    }
+
+    async fn sync_remote(&self, id: &str) -> Result<Option<Remote>, Error> {
+        let root = match self.fetch(id).await {
+            Ok(root) => root,
+            Err(err) if err.status() == Some(StatusCode::NOT_FOUND) => return Ok(None),
+            Err(err) => return Err(err),
+        };
+        let de::Revision(revision) = serde_json::from_slice(&root).map_err(Error::from_json)?;
+        Ok(Some(Remote {
+            id: id.to_owned(),
+            revision,
+            root,
+        }))
+    }
+
+    async fn sync_plan(
+        &self,
+        base: Option<&de::SyncEntry>,
+        local: Option<&String>,
+        remote: Option<&Remote>,
+    ) -> Result<Plan, Error> {
+        let sha256 = local.map(|local| format::sha256(local.as_bytes()));
+        match (plan(base, sha256, remote), local, remote) {
+            (Plan::Compare(entry), Some(local), Some(remote)) => {
+                let (content, _) = self.assemble(&remote.root).await?;
+                Ok(if content == *local {
+                    Plan::Record(entry)
+                } else {
+                    Plan::Change(SyncAction::Conflict)
+                })
+            }
+            (plan, _, _) => Ok(plan),
+        }
+    }
+
+    async fn sync_upload(&self, id: Option<&str>, content: &str) -> Result<de::SyncEntry, Error> {
+        let id = match id {
+            Some(id) => {
+                self.set(id, content).await?;
+                id.to_owned()
+            }
+            None => self.insert(content).await?,
+        };
+        let remote = self
+            .sync_remote(&id)
+            .await?
+            .ok_or_else(|| Error::from_status(StatusCode::NOT_FOUND))?;
+        Ok(de::SyncEntry {
+            id,
+            sha256: format::sha256(content.as_bytes()),
+            revision: remote.revision,
+        })
+    }
+
+    async fn sync_download(&self, path: &Path, remote: &Remote) -> Result<de::SyncEntry, Error> {
+        let (content, _) = self.assemble(&remote.root).await?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await.map_err(io_error)?;
+        }
+        tokio::fs::write(path, &content).await.map_err(io_error)?;
+        Ok(de::SyncEntry {
+            id: remote.id.clone(),
+            sha256: format::sha256(content.as_bytes()),
+            revision: remote.revision.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{key_path, plan, Plan, Remote, SyncAction, STATE_FILENAME};
+    use crate::de;
+    use hyper::body::Bytes;
+    use std::path::Path;
+
+    fn entry(id: &str, sha256: &str, revision: &str) -> de::SyncEntry {
+        de::SyncEntry {
+            id: id.to_owned(),
+            sha256: sha256.to_owned(),
+            revision: revision.to_owned(),
+        }
+    }
+
+    fn remote(id: &str, revision: &str) -> Remote {
+        Remote {
+            id: id.to_owned(),
+            revision: revision.to_owned(),
+            root: Bytes::new().into(),
+        }
+    }
+
+    fn action(plan: Plan) -> Option<SyncAction> {
+        match plan {
+            Plan::Change(action) => Some(action),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn key_path_stays_inside_dir() {
+        let dir = Path::new("sync");
+        assert_eq!(
+            key_path(dir, "a/b.txt").unwrap(),
+            dir.join("a").join("b.txt")
+        );
+        for key in [
+            "",
+            "/etc/passwd",
+            "a//b",
+            "a/",
+            ".",
+            "..",
+            "../a",
+            "a/../../b",
+            "a/./b",
+            "a\\..\\b",
+            STATE_FILENAME,
+        ] {
+            assert!(key_path(dir, key).unwrap_err().is_invalid(), "{key}");
+        }
+    }
+
+    #[test]
+    fn plans_one_sided_changes() {
+        let base = entry("id", "old", "1");
+        assert!(matches!(
+            plan(
+                Some(&base),
+                Some("old".to_owned()),
+                Some(&remote("id", "1"))
+            ),
+            Plan::Keep
+        ));
+        assert_eq!(
+            action(plan(
+                Some(&base),
+                Some("new".to_owned()),
+                Some(&remote("id", "1"))
+            )),
+            Some(SyncAction::Upload)
+        );
+        assert_eq!(
+            action(plan(None, Some("new".to_owned()), None)),
+            Some(SyncAction::Upload)
+        );
+        assert_eq!(
+            action(plan(Some(&base), None, Some(&remote("id", "1")))),
+            Some(SyncAction::DeleteRemote)
+        );
+        assert_eq!(
+            action(plan(
+                Some(&base),
+                Some("old".to_owned()),
+                Some(&remote("id", "2"))
+            )),
+            Some(SyncAction::Download)
+        );
+        assert_eq!(
+            action(plan(
+                Some(&base),
+                Some("old".to_owned()),
+                Some(&remote("other", "1"))
+            )),
+            Some(SyncAction::Download)
+        );
+        assert_eq!(
+            action(plan(None, None, Some(&remote("id", "1")))),
+            Some(SyncAction::Download)
+        );
+        assert_eq!(
+            action(plan(Some(&base), Some("old".to_owned()), None)),
+            Some(SyncAction::DeleteLocal)
+        );
+        assert!(matches!(plan(Some(&base), None, None), Plan::Forget));
+        assert!(matches!(plan(None, None, None), Plan::Keep));
+    }
+
+    #[test]
+    fn plans_conflicting_changes() {
+        let base = entry("id", "old", "1");
+        assert!(matches!(
+            plan(Some(&base), Some("new".to_owned()), Some(&remote("id", "2"))),
+            Plan::Compare(entry) if entry.id == "id" && entry.sha256 == "new" && entry.revision == "2"
+        ));
+        assert!(matches!(
+            plan(None, Some("new".to_owned()), Some(&remote("id", "1"))),
+            Plan::Compare(_)
+        ));
+        assert_eq!(
+            action(plan(Some(&base), None, Some(&remote("id", "2")))),
+            Some(SyncAction::Conflict)
+        );
+        assert_eq!(
+            action(plan(Some(&base), Some("new".to_owned()), None)),
+            Some(SyncAction::Conflict)
+        );
+    }
+}
@@ -0,0 +1,23 @@
+use std::time::{Duration, SystemTime};
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Trashed {
+    pub(super) id: String,
+    pub(super) trashed_at: u64,
+}
+
+impl Trashed {
+    #[inline]
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    #[inline]
+    pub fn trashed_at(&self) -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_secs(self.trashed_at)
+    }
+
+    pub(super) fn is_older_than(&self, retention: Duration, now: u64) -> bool {
+        self.trashed_at.saturating_add(retention.as_secs()) <= now
+    }
+}
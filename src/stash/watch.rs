@@ -0,0 +1,234 @@
+use super::{pager::Pager, Error, Stash};
+use crate::{de, format};
+use futures_core::Stream;
+use futures_util::stream;
+use hyper::header::HeaderValue;
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt::{self, Debug},
+    pin::Pin,
+    task::{self, Poll},
+};
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Event {
+    Created { id: String, revision: String },
+    Updated { id: String, revision: String },
+    Deleted { id: String },
+}
+
+impl Event {
+    pub fn id(&self) -> &str {
+        match self {
+            Self::Created { id, .. } | Self::Updated { id, .. } | Self::Deleted { id } => id,
+        }
+    }
+
+    pub fn revision(&self) -> Option<&str> {
+        match self {
+            Self::Created { revision, .. } | Self::Updated { revision, .. } => Some(revision),
+            Self::Deleted { .. } => None,
+        }
+    }
+}
+
+pub struct Watch<'a>(Pin<Box<dyn Stream<Item = Result<Event, Error>> + Send + 'a>>);
+
+impl Debug for Watch<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Watch").finish_non_exhaustive()
+    }
+}
+
+impl Stream for Watch<'_> {
+    type Item = Result<Event, Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut().0.as_mut().poll_next(cx)
+    }
+}
+
+struct One<'a> {
+    stash: &'a Stash,
+    id: String,
+    etag: Option<HeaderValue>,
+    revision: Option<String>,
+    expires_at: Option<u64>,
+    started: bool,
+    deleted: bool,
+}
+
+struct Page {
+    etag: Option<HeaderValue>,
+    listed_len: usize,
+    revisions: Vec<(String, String)>,
+}
+
+struct All<'a> {
+    stash: &'a Stash,
+    pages: Vec<Page>,
+    revisions: Option<HashMap<String, String>>,
+    events: VecDeque<Event>,
+    started: bool,
+}
+
+impl<'a> Watch<'a> {
+    pub(super) fn one(stash: &'a Stash, id: &str) -> Self {
+        let state = One {
+            stash,
+            id: id.to_owned(),
+            etag: None,
+            revision: None,
+            expires_at: None,
+            started: false,
+            deleted: false,
+        };
+        Self(Box::pin(stream::unfold(state, |mut state| async move {
+            if state.deleted {
+                return None;
+            }
+            loop {
+                state.tick().await;
+                match state.poll().await {
+                    Ok(None) => {}
+                    Ok(Some(event)) => return Some((Ok(event), state)),
+                    Err(err) => return Some((Err(err), state)),
+                }
+            }
+        })))
+    }
+
+    pub(super) fn all(stash: &'a Stash) -> Self {
+        let state = All {
+            stash,
+            pages: Vec::new(),
+            revisions: None,
+            events: VecDeque::new(),
+            started: false,
+        };
+        Self(Box::pin(stream::unfold(state, |mut state| async move {
+            loop {
+                if let Some(event) = state.events.pop_front() {
+                    return Some((Ok(event), state));
+                }
+                state.tick().await;
+                if let Err(err) = state.poll().await {
+                    return Some((Err(err), state));
+                }
+            }
+        })))
+    }
+}
+
+impl One<'_> {
+    async fn tick(&mut self) {
+        if self.started {
+            tokio::time::sleep(self.stash.config.watch_interval).await;
+        }
+        self.started = true;
+    }
+
+    fn is_expired(&self) -> bool {
+        self.expires_at
+            .is_some_and(|expires_at| expires_at <= format::unix_time())
+    }
+
+    fn delete(&mut self) -> Event {
+        self.deleted = true;
+        self.stash.forget(&self.id);
+        Event::Deleted {
+            id: self.id.clone(),
+        }
+    }
+
+    async fn poll(&mut self) -> Result<Option<Event>, Error> {
+        let client = self.stash.client.gist(&self.id).map_err(Error::from_http)?;
+        let (body, etag) = match Stash::revalidate(&client, self.etag.clone()).await {
+            Ok(None) if self.is_expired() => return Ok(Some(self.delete())),
+            Ok(None) => return Ok(None),
+            Ok(Some(resp)) => resp,
+            Err(err) if err.status() == Some(hyper::StatusCode::NOT_FOUND) => {
+                return Ok(Some(self.delete()));
+            }
+            Err(err) => return Err(err),
+        };
+        let current = serde_json::from_slice::<de::FilesLen>(&body).map_err(Error::from_json)?;
+        self.etag = etag;
+        self.expires_at = current.manifest().and_then(|manifest| manifest.expires_at);
+        if current.trashed_at().is_some() || self.is_expired() {
+            return Ok(Some(self.delete()));
+        }
+        let revision = current.updated_at().to_owned();
+        match self.revision.replace(revision.clone()) {
+            Some(previous) if previous != revision => {
+                self.stash.forget(&self.id);
+                Ok(Some(Event::Updated {
+                    id: self.id.clone(),
+                    revision,
+                }))
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+impl All<'_> {
+    async fn tick(&mut self) {
+        if self.started {
+            tokio::time::sleep(self.stash.config.watch_interval).await;
+        }
+        self.started = true;
+    }
+
+    async fn poll(&mut self) -> Result<(), Error> {
+        let mut pager = Pager::new(self.stash, None);
+        while !pager.is_exhausted() {
+            let index = pager.pages();
+            let cached = self
+                .pages
+                .get(index)
+                .and_then(|page| page.etag.clone().map(|etag| (etag, page.listed_len)));
+            if let Some((revisions, etag)) = pager.revalidate::<de::Revisions>(cached).await? {
+                let page = Page {
+                    etag,
+                    listed_len: revisions.listed_len,
+                    revisions: revisions.revisions,
+                };
+                match self.pages.get_mut(index) {
+                    Some(current) => *current = page,
+                    None => self.pages.push(page),
+                }
+            }
+        }
+        self.pages.truncate(pager.pages());
+        let current = self
+            .pages
+            .iter()
+            .flat_map(|page| page.revisions.iter().cloned())
+            .collect::<HashMap<_, _>>();
+        if let Some(previous) = &self.revisions {
+            for (id, revision) in self.pages.iter().flat_map(|page| &page.revisions) {
+                match previous.get(id) {
+                    None => self.events.push_back(Event::Created {
+                        id: id.clone(),
+                        revision: revision.clone(),
+                    }),
+                    Some(previous) if previous != revision => {
+                        self.stash.forget(id);
+                        self.events.push_back(Event::Updated {
+                            id: id.clone(),
+                            revision: revision.clone(),
+                        });
+                    }
+                    Some(_) => {}
+                }
+            }
+            for id in previous.keys().filter(|id| !current.contains_key(*id)) {
+                self.stash.forget(id);
+                self.events.push_back(Event::Deleted { id: id.clone() });
+            }
+        }
+        self.revisions = Some(current);
+        Ok(())
+    }
+}
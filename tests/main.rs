@@ -1,6 +1,6 @@
 use futures_util::StreamExt as _;
 use std::env;
-use tokio::task::JoinSet;
+use tokio::{io::AsyncReadExt as _, task::JoinSet};
 
 fn stash() -> octostash::Stash {
     octostash::Stash::new(
@@ -12,6 +12,38 @@ fn stash() -> octostash::Stash {
     )
 }
 
+async fn gist_request(
+    method: hyper::Method,
+    id: &str,
+    body: hyper::Body,
+) -> hyper::Response<hyper::Body> {
+    let token = env::var("OCTOSTASH_DEV_PERSONAL_ACCESS_TOKEN")
+        .expect("OCTOSTASH_DEV_PERSONAL_ACCESS_TOKEN");
+    let client = hyper::Client::builder().build(hyper_tls::HttpsConnector::new());
+    let request = hyper::Request::builder()
+        .method(method)
+        .uri(format!("https://api.github.com/gists/{id}"))
+        .header(hyper::header::AUTHORIZATION, format!("Bearer {token}"))
+        .header(hyper::header::USER_AGENT, "octostash")
+        .header(hyper::header::ACCEPT, "application/vnd.github+json")
+        .body(body)
+        .unwrap();
+    let response = client.request(request).await.unwrap();
+    assert!(response.status().is_success());
+    response
+}
+
+async fn patch_gist(id: &str, body: &'static str) {
+    gist_request(hyper::Method::PATCH, id, hyper::Body::from(body)).await;
+}
+
+async fn gist_description(id: &str) -> Option<String> {
+    let response = gist_request(hyper::Method::GET, id, hyper::Body::empty()).await;
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let gist = serde_json::from_slice::<serde_json::Value>(&body).unwrap();
+    gist["description"].as_str().map(str::to_owned)
+}
+
 #[tokio::test]
 async fn insert() {
     let stash = stash();
@@ -58,3 +90,815 @@ async fn clear() {
     }
     assert!(stash.ids().next().await.is_none());
 }
+
+#[tokio::test]
+async fn get_range() {
+    let stash = stash();
+    let id = stash.insert("Hello, octostash!").await.unwrap();
+    assert_eq!(stash.get_range(&id, ..5).await.unwrap(), "Hello");
+    assert_eq!(stash.get_range(&id, 7..).await.unwrap(), "octostash!");
+    assert_eq!(stash.get_range(&id, 7..=15).await.unwrap(), "octostash");
+    assert_eq!(stash.get_range(&id, 100..).await.unwrap(), "");
+    stash.remove(&id).await.unwrap();
+    let id = stash.insert("Héllo, octostash!").await.unwrap();
+    assert_eq!(stash.get_range(&id, ..2).await.unwrap(), "H");
+    assert_eq!(stash.get_range(&id, 1..3).await.unwrap(), "é");
+    assert_eq!(stash.get_range(&id, 2..6).await.unwrap(), "llo");
+    stash.remove(&id).await.unwrap();
+}
+
+#[tokio::test]
+async fn get_stream() {
+    let stash = stash();
+    let id = stash.insert("Hello, octostash!").await.unwrap();
+    let chunks = stash
+        .get_stream(&id)
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<Result<String, _>>()
+        .unwrap();
+    assert_eq!(chunks, "Hello, octostash!");
+    let mut value = String::new();
+    stash
+        .get_stream(&id)
+        .into_async_read()
+        .read_to_string(&mut value)
+        .await
+        .unwrap();
+    assert_eq!(value, "Hello, octostash!");
+    stash.remove(&id).await.unwrap();
+}
+
+#[tokio::test]
+async fn insert_stream() {
+    let stash = stash();
+    let id = stash
+        .insert_stream(futures_util::stream::iter(
+            ["Hello, ", "octo", "stash!"].map(|chunk| {
+                Ok::<_, std::io::Error>(octostash::stash::Bytes::from_static(chunk.as_bytes()))
+            }),
+        ))
+        .await
+        .unwrap();
+    let value = stash.get(&id).await.unwrap();
+    assert_eq!(value, "Hello, octostash!");
+    stash
+        .set_reader(&id, "Hello, \"octostash\"!\n".as_bytes())
+        .await
+        .unwrap();
+    let value = stash.get(&id).await.unwrap();
+    assert_eq!(value, "Hello, \"octostash\"!\n");
+    stash.remove(&id).await.unwrap();
+}
+
+#[ignore]
+#[tokio::test]
+async fn spanning() {
+    let stash = stash();
+    let value = "octostash".repeat(10_000_000);
+    let id = stash.insert(&value).await.unwrap();
+    assert_eq!(stash.get(&id).await.unwrap(), value);
+    assert_eq!(stash.get_range(&id, ..9).await.unwrap(), "octostash");
+    stash.set(&id, "Hello, octostash!").await.unwrap();
+    assert_eq!(stash.get(&id).await.unwrap(), "Hello, octostash!");
+    stash.set(&id, &value).await.unwrap();
+    assert_eq!(stash.get(&id).await.unwrap(), value);
+    stash.remove(&id).await.unwrap();
+    let err = stash.get(&id).await.unwrap_err();
+    assert_eq!(err.status(), Some(hyper::StatusCode::NOT_FOUND));
+}
+
+#[tokio::test]
+async fn spanning_children() {
+    let stash = stash().with_chunking(octostash::Chunking::bytes(4));
+    let value = (0..700)
+        .map(|index| format!("{index:04}"))
+        .collect::<String>();
+    let id = stash.insert(&value).await.unwrap();
+    assert_eq!(stash.get(&id).await.unwrap(), value);
+    assert_eq!(stash.get_range(&id, 1192..1200).await.unwrap(), "02980299");
+    assert_eq!(stash.metadata(&id).await.unwrap().raw_urls().len(), 700);
+    let reversed = (0..700)
+        .rev()
+        .map(|index| format!("{index:04}"))
+        .collect::<String>();
+    stash.set(&id, &reversed).await.unwrap();
+    assert_eq!(stash.get(&id).await.unwrap(), reversed);
+    stash
+        .set_reader(&id, std::io::Cursor::new(value.clone()))
+        .await
+        .unwrap();
+    assert_eq!(stash.get(&id).await.unwrap(), value);
+    stash.set(&id, "Hello, octostash!").await.unwrap();
+    assert_eq!(stash.get(&id).await.unwrap(), "Hello, octostash!");
+    stash.remove(&id).await.unwrap();
+}
+
+#[tokio::test]
+async fn integrity() {
+    let stash = stash().with_chunk_hashes(true);
+    let id = stash.insert("Hello, octostash!").await.unwrap();
+    assert_eq!(stash.get(&id).await.unwrap(), "Hello, octostash!");
+    assert_eq!(stash.get_range(&id, 7..).await.unwrap(), "octostash!");
+    let chunks = stash
+        .get_stream(&id)
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<Result<String, _>>()
+        .unwrap();
+    assert_eq!(chunks, "Hello, octostash!");
+    let stash = stash.with_verify(false);
+    assert_eq!(stash.get(&id).await.unwrap(), "Hello, octostash!");
+    stash.remove(&id).await.unwrap();
+}
+
+#[tokio::test]
+async fn empty() {
+    let stash = stash();
+    let id = stash.insert("").await.unwrap();
+    assert_eq!(stash.get(&id).await.unwrap(), "");
+    stash.set(&id, "Hello, octostash!").await.unwrap();
+    assert_eq!(stash.get(&id).await.unwrap(), "Hello, octostash!");
+    stash.set(&id, "").await.unwrap();
+    assert_eq!(stash.get(&id).await.unwrap(), "");
+    assert_eq!(stash.get_range(&id, ..).await.unwrap(), "");
+    stash.remove(&id).await.unwrap();
+    let id = stash
+        .insert_stream(futures_util::stream::empty::<Result<_, std::io::Error>>())
+        .await
+        .unwrap();
+    assert_eq!(stash.get(&id).await.unwrap(), "");
+    stash.remove(&id).await.unwrap();
+}
+
+#[tokio::test]
+async fn chunking() {
+    let value = "Hello, octostash!\n".repeat(8);
+    for chunking in [
+        octostash::Chunking::bytes(20),
+        octostash::Chunking::chars(40).lines(),
+        octostash::Chunking::bytes(64).content_defined(),
+        octostash::Chunking::bytes(0),
+    ] {
+        let stash = stash().with_chunking(chunking);
+        let id = stash.insert(&value).await.unwrap();
+        assert_eq!(stash.get(&id).await.unwrap(), value);
+        stash
+            .set_reader(&id, std::io::Cursor::new(value.clone()))
+            .await
+            .unwrap();
+        assert_eq!(stash.get(&id).await.unwrap(), value);
+        stash.remove(&id).await.unwrap();
+    }
+}
+
+#[tokio::test]
+async fn set_stream_rollback() {
+    let stash = stash().with_chunking(octostash::Chunking::bytes(4));
+    let id = stash.insert("old value").await.unwrap();
+    let files = stash.gist_files(&id).await.unwrap();
+    let value = (0..400)
+        .map(|index| format!("{index:04}"))
+        .collect::<String>();
+    stash
+        .set_stream(
+            &id,
+            futures_util::stream::iter([
+                Ok(octostash::stash::Bytes::from(value)),
+                Err(std::io::Error::other("stream failed")),
+            ]),
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(stash.get(&id).await.unwrap(), "old value");
+    assert_eq!(stash.gist_files(&id).await.unwrap(), files);
+    stash.remove(&id).await.unwrap();
+}
+
+#[tokio::test]
+async fn chunking_stable_names() {
+    let value = (0..200)
+        .map(|index| format!("line {index} of octostash\n"))
+        .collect::<String>();
+    let stash = stash().with_chunking(octostash::Chunking::bytes(256).content_defined());
+    let id = stash.insert(&value).await.unwrap();
+    let before = stash.gist_files(&id).await.unwrap();
+    let edited = format!("an edit near the start\n{value}");
+    stash.set(&id, &edited).await.unwrap();
+    assert_eq!(stash.get(&id).await.unwrap(), edited);
+    let after = stash.gist_files(&id).await.unwrap();
+    let kept = before
+        .keys()
+        .filter(|name| *name != "octostash.json" && after.contains_key(*name))
+        .count();
+    assert!(kept * 2 > before.len());
+    stash
+        .set_reader(&id, std::io::Cursor::new(value.clone()))
+        .await
+        .unwrap();
+    assert_eq!(stash.get(&id).await.unwrap(), value);
+    stash.remove(&id).await.unwrap();
+}
+
+#[tokio::test]
+async fn gist_files() {
+    let stash = stash().with_strict(true);
+    let id = stash.insert("Hello, octostash!").await.unwrap();
+    assert_eq!(stash.get(&id).await.unwrap(), "Hello, octostash!");
+    let files = stash.gist_files(&id).await.unwrap();
+    assert_eq!(files.len(), 2);
+    assert!(files.contains_key("octostash.json"));
+    assert!(files.values().any(|content| content == "Hello, octostash!"));
+    stash.remove(&id).await.unwrap();
+}
+
+#[tokio::test]
+async fn files() {
+    let stash = stash().with_strict(true);
+    let files = std::collections::BTreeMap::from([
+        (
+            "config.toml".to_owned(),
+            "name = \"octostash\"\n".to_owned(),
+        ),
+        ("empty".to_owned(), String::new()),
+        (
+            "schema_1.json".to_owned(),
+            "{\"type\": \"object\"}".to_owned(),
+        ),
+    ]);
+    let id = stash.insert_files(&files).await.unwrap();
+    assert_eq!(stash.get_files(&id).await.unwrap(), files);
+    assert_eq!(stash.metadata(&id).await.unwrap().raw_urls().len(), 2);
+    stash.remove(&id).await.unwrap();
+    assert!(stash
+        .insert_files(&std::collections::BTreeMap::new())
+        .await
+        .unwrap_err()
+        .is_invalid());
+    let files = std::collections::BTreeMap::from([(String::new(), String::new())]);
+    assert!(stash.insert_files(&files).await.unwrap_err().is_invalid());
+    let id = stash.insert("Hello, octostash!").await.unwrap();
+    assert!(stash.get_files(&id).await.unwrap_err().is_not_bundle());
+    stash.remove(&id).await.unwrap();
+}
+
+#[tokio::test]
+async fn files_set() {
+    let stash = stash();
+    let files = std::collections::BTreeMap::from([(
+        "config.toml".to_owned(),
+        "name = \"octostash\"\n".to_owned(),
+    )]);
+    let id = stash.insert_files(&files).await.unwrap();
+    assert!(stash
+        .set(&id, "Hello, octostash!")
+        .await
+        .unwrap_err()
+        .is_bundle());
+    assert!(stash.get(&id).await.unwrap_err().is_bundle());
+    assert_eq!(stash.get_files(&id).await.unwrap(), files);
+    stash.remove(&id).await.unwrap();
+}
+
+#[tokio::test]
+async fn visibility() {
+    use octostash::stash::Visibility;
+    let stash = stash();
+    let id = stash
+        .insert_with_visibility("Hello, octostash!", Visibility::Public)
+        .await
+        .unwrap();
+    let metadata = stash.metadata(&id).await.unwrap();
+    assert_eq!(metadata.id(), id);
+    assert_eq!(metadata.visibility(), Visibility::Public);
+    assert!(metadata.html_url().ends_with(&id));
+    assert_eq!(metadata.raw_urls().len(), 1);
+    stash.remove(&id).await.unwrap();
+    let id = stash.insert("Hello, octostash!").await.unwrap();
+    let metadata = stash.metadata(&id).await.unwrap();
+    assert_eq!(metadata.visibility(), Visibility::Secret);
+    stash.remove(&id).await.unwrap();
+}
+
+#[tokio::test]
+async fn listing() {
+    let stash = stash();
+    let id = stash.insert("Hello, octostash!").await.unwrap();
+    let mut listing = stash.listing();
+    let mut found = None;
+    while let Some(listed) = listing.next().await {
+        let listed = listed.unwrap();
+        if listed.id() == id {
+            found = Some(listed);
+        }
+    }
+    let listed = found.unwrap();
+    assert_eq!(listed.visibility(), octostash::stash::Visibility::Secret);
+    assert!(listed.html_url().ends_with(&id));
+    assert!(!listed.created_at().is_empty());
+    stash.remove(&id).await.unwrap();
+}
+
+#[tokio::test]
+async fn read_only() {
+    let stash = stash();
+    let id = stash
+        .insert_with_visibility("Hello, octostash!", octostash::stash::Visibility::Public)
+        .await
+        .unwrap();
+    let read_only = octostash::ReadOnlyStash::new();
+    assert_eq!(read_only.get(&id).await.unwrap(), "Hello, octostash!");
+    assert_eq!(read_only.metadata(&id).await.unwrap().id(), id);
+    stash.remove(&id).await.unwrap();
+    let mut ids = read_only.user_ids("../gists");
+    assert!(matches!(ids.next().await, Some(Err(err)) if err.is_invalid()));
+}
+
+#[tokio::test]
+async fn raw_reads() {
+    let stash = stash().with_raw_reads(true);
+    let id = stash.insert("Hello, octostash!").await.unwrap();
+    assert_eq!(stash.get(&id).await.unwrap(), "Hello, octostash!");
+    assert_eq!(stash.get(&id).await.unwrap(), "Hello, octostash!");
+    assert_eq!(stash.get_range(&id, 7..).await.unwrap(), "octostash!");
+    stash.set(&id, "Hello again, octostash!").await.unwrap();
+    assert_eq!(stash.get(&id).await.unwrap(), "Hello again, octostash!");
+    let reader = crate::stash()
+        .with_raw_reads(true)
+        .with_raw_reads_ttl(std::time::Duration::ZERO);
+    assert_eq!(reader.get(&id).await.unwrap(), "Hello again, octostash!");
+    stash.set(&id, "Hello, octostash!").await.unwrap();
+    assert_eq!(reader.get(&id).await.unwrap(), "Hello, octostash!");
+    stash.remove(&id).await.unwrap();
+}
+
+#[tokio::test]
+async fn etags() {
+    let stash = stash().with_etags(true);
+    let id = stash.insert("Hello, octostash!").await.unwrap();
+    assert_eq!(stash.get(&id).await.unwrap(), "Hello, octostash!");
+    assert_eq!(stash.get(&id).await.unwrap(), "Hello, octostash!");
+    stash.set(&id, "Hello again, octostash!").await.unwrap();
+    assert_eq!(stash.get(&id).await.unwrap(), "Hello again, octostash!");
+    for _ in 0..2 {
+        assert!(!stash.ids().next().await.unwrap().unwrap().is_empty());
+    }
+    stash.remove(&id).await.unwrap();
+}
+
+#[tokio::test]
+async fn cache() {
+    let stash = stash().with_cache(
+        octostash::stash::Cache::entries(16).with_ttl(std::time::Duration::from_secs(60)),
+    );
+    let id = stash.insert("Hello, octostash!").await.unwrap();
+    assert_eq!(stash.get(&id).await.unwrap(), "Hello, octostash!");
+    assert_eq!(stash.clone().get(&id).await.unwrap(), "Hello, octostash!");
+    stash.set(&id, "Hello again, octostash!").await.unwrap();
+    assert_eq!(stash.get(&id).await.unwrap(), "Hello again, octostash!");
+    let stats = stash.cache_stats().unwrap();
+    assert_eq!((stats.hits(), stats.misses()), (1, 2));
+    stash.remove(&id).await.unwrap();
+}
+
+#[tokio::test]
+async fn disk_cache() {
+    let dir = env::temp_dir().join(format!("octostash-disk-cache-{}", std::process::id()));
+    let disk_cache = octostash::stash::DiskCache::new(&dir).with_stale_if_error(true);
+    let stash = stash().with_disk_cache(disk_cache.clone());
+    let id = stash.insert("Hello, octostash!").await.unwrap();
+    assert_eq!(
+        stash.get_with_freshness(&id).await.unwrap(),
+        (
+            "Hello, octostash!".to_owned(),
+            octostash::stash::Freshness::Fresh
+        )
+    );
+    assert_eq!(stash.get(&id).await.unwrap(), "Hello, octostash!");
+    assert!(dir.join(&id).is_dir());
+    stash.set(&id, "Hello again, octostash!").await.unwrap();
+    assert_eq!(stash.get(&id).await.unwrap(), "Hello again, octostash!");
+    stash.remove(&id).await.unwrap();
+    assert!(!dir.join(&id).exists());
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+#[tokio::test]
+async fn disk_cache_max_bytes() {
+    let dir = env::temp_dir().join(format!(
+        "octostash-disk-cache-max-bytes-{}",
+        std::process::id()
+    ));
+    let writer = stash();
+    let mut ids = Vec::new();
+    for index in 0..3 {
+        ids.push(
+            writer
+                .insert(&index.to_string().repeat(1000))
+                .await
+                .unwrap(),
+        );
+    }
+    let stash =
+        stash().with_disk_cache(octostash::stash::DiskCache::new(&dir).with_max_bytes(2500));
+    let cached = |id: &str| dir.join(id).is_dir();
+    for id in &ids[..2] {
+        stash.get(id).await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(1100)).await;
+    }
+    assert!(cached(&ids[0]) && cached(&ids[1]));
+    stash.get(&ids[2]).await.unwrap();
+    assert!(!cached(&ids[0]));
+    assert!(cached(&ids[1]) && cached(&ids[2]));
+    tokio::time::sleep(std::time::Duration::from_millis(1100)).await;
+    assert_eq!(stash.get(&ids[0]).await.unwrap(), "0".repeat(1000));
+    assert!(cached(&ids[0]) && cached(&ids[2]));
+    assert!(!cached(&ids[1]));
+    for id in &ids {
+        writer.remove(id).await.unwrap();
+    }
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+#[tokio::test]
+async fn coalescing() {
+    let stash = stash();
+    let id = stash.insert("Hello, octostash!").await.unwrap();
+    let mut set = JoinSet::new();
+    for _ in 0..8 {
+        let stash = stash.clone();
+        let id = id.clone();
+        set.spawn(async move { stash.get(&id).await.unwrap() });
+    }
+    let before = stash.flight_stats();
+    while let Some(value) = set.join_next().await {
+        assert_eq!(value.unwrap(), "Hello, octostash!");
+    }
+    let after = stash.flight_stats();
+    assert!(after.coalesced() > before.coalesced());
+    assert!(after.requests() - before.requests() < 8);
+    stash.set(&id, "Hello again, octostash!").await.unwrap();
+    assert_eq!(stash.get(&id).await.unwrap(), "Hello again, octostash!");
+    stash.remove(&id).await.unwrap();
+}
+
+#[tokio::test]
+async fn watch() {
+    let stash = stash().with_watch_interval(std::time::Duration::from_secs(1));
+    let id = stash.insert("Hello, octostash!").await.unwrap();
+    let writer = stash.clone();
+    let writer_id = id.clone();
+    let write = tokio::spawn(async move {
+        tokio::time::sleep(std::time::Duration::from_secs(3)).await;
+        writer
+            .set(&writer_id, "Hello again, octostash!")
+            .await
+            .unwrap();
+        tokio::time::sleep(std::time::Duration::from_secs(3)).await;
+        writer.remove(&writer_id).await.unwrap();
+    });
+    let mut watch = stash.watch(&id);
+    match watch.next().await.unwrap().unwrap() {
+        octostash::stash::Event::Updated {
+            id: updated,
+            revision,
+        } => {
+            assert_eq!(updated, id);
+            assert_eq!(revision, stash.metadata(&id).await.unwrap().updated_at());
+        }
+        event => panic!("unexpected event: {event:?}"),
+    }
+    assert_eq!(
+        watch.next().await.unwrap().unwrap(),
+        octostash::stash::Event::Deleted { id: id.clone() }
+    );
+    assert!(watch.next().await.is_none());
+    write.await.unwrap();
+}
+
+#[tokio::test]
+async fn watch_trashed() {
+    let stash = stash()
+        .with_soft_delete(true)
+        .with_watch_interval(std::time::Duration::from_secs(1));
+    let id = stash.insert("Hello, octostash!").await.unwrap();
+    let writer = stash.clone();
+    let writer_id = id.clone();
+    let write = tokio::spawn(async move {
+        tokio::time::sleep(std::time::Duration::from_secs(3)).await;
+        writer.remove(&writer_id).await.unwrap();
+    });
+    let mut watch = stash.watch(&id);
+    assert_eq!(
+        watch.next().await.unwrap().unwrap(),
+        octostash::stash::Event::Deleted { id: id.clone() }
+    );
+    assert!(watch.next().await.is_none());
+    drop(watch);
+    write.await.unwrap();
+    stash.restore(&id).await.unwrap();
+    stash.with_soft_delete(false).remove(&id).await.unwrap();
+}
+
+#[tokio::test]
+async fn ttl() {
+    let stash = stash();
+    let id = stash
+        .insert_with_ttl("Hello, octostash!", std::time::Duration::from_secs(3600))
+        .await
+        .unwrap();
+    assert_eq!(stash.get(&id).await.unwrap(), "Hello, octostash!");
+    let expired = stash
+        .insert_with_ttl("Hello, octostash!", std::time::Duration::ZERO)
+        .await
+        .unwrap();
+    assert_eq!(
+        stash.get(&expired).await.unwrap_err().status(),
+        Some(octostash::stash::StatusCode::NOT_FOUND)
+    );
+    assert_eq!(
+        stash.metadata(&expired).await.unwrap_err().status(),
+        Some(octostash::stash::StatusCode::NOT_FOUND)
+    );
+    let report = stash.gc().await.unwrap();
+    assert!(report.removed().contains(&expired));
+    assert!(!report.removed().contains(&id));
+    stash.remove(&id).await.unwrap();
+}
+
+#[tokio::test]
+async fn trash() {
+    let stash = stash().with_soft_delete(true);
+    let id = stash.insert("Hello, octostash!").await.unwrap();
+    stash.remove(&id).await.unwrap();
+    assert_eq!(
+        stash.get(&id).await.unwrap_err().status(),
+        Some(octostash::stash::StatusCode::NOT_FOUND)
+    );
+    assert_eq!(
+        stash.metadata(&id).await.unwrap_err().status(),
+        Some(octostash::stash::StatusCode::NOT_FOUND)
+    );
+    assert!(stash
+        .trashed()
+        .await
+        .unwrap()
+        .iter()
+        .any(|trashed| trashed.id() == id));
+    stash.restore(&id).await.unwrap();
+    assert_eq!(stash.get(&id).await.unwrap(), "Hello, octostash!");
+    stash.remove(&id).await.unwrap();
+    let report = stash
+        .purge(std::time::Duration::from_secs(3600))
+        .await
+        .unwrap();
+    assert!(!report.removed().contains(&id));
+    let report = stash.purge(std::time::Duration::ZERO).await.unwrap();
+    assert!(report.removed().contains(&id));
+}
+
+#[tokio::test]
+async fn trash_keeps_description() {
+    let stash = stash().with_soft_delete(true);
+    let id = stash.insert("Hello, octostash!").await.unwrap();
+    patch_gist(&id, r#"{"description": "kept across trash"}"#).await;
+    stash.remove(&id).await.unwrap();
+    assert!(gist_description(&id)
+        .await
+        .unwrap()
+        .ends_with(" kept across trash"));
+    stash.restore(&id).await.unwrap();
+    assert_eq!(
+        gist_description(&id).await.as_deref(),
+        Some("kept across trash")
+    );
+    stash.with_soft_delete(false).remove(&id).await.unwrap();
+}
+
+#[tokio::test]
+async fn export_import() {
+    let stash = stash();
+    let id = stash.insert("Hello, octostash!").await.unwrap();
+    let mut archive = Vec::new();
+    assert!(stash.export(&mut archive).await.unwrap() >= 1);
+    let line = archive
+        .split(|byte| *byte == b'\n')
+        .find(|line| line.starts_with(format!("{{\"id\":\"{id}\"").as_bytes()))
+        .unwrap();
+    let report = stash.import(line).await;
+    assert!(report.failed().is_empty());
+    let imported = &report.imported()[&id];
+    assert_ne!(imported, &id);
+    assert_eq!(stash.get(imported).await.unwrap(), "Hello, octostash!");
+    stash.remove(&id).await.unwrap();
+    stash.remove(imported).await.unwrap();
+    let report = stash.import(&b"\n{\"id\": 1}\n"[..]).await;
+    assert!(report.imported().is_empty());
+    assert_eq!(report.failed().len(), 1);
+    assert_eq!(report.failed()[0].0, 2);
+}
+
+#[tokio::test]
+async fn check() {
+    use octostash::stash::Health;
+    let stash = stash().with_check_concurrency(4);
+    let id = stash.insert("Hello, octostash!").await.unwrap();
+    let report = stash.check().await.unwrap();
+    let entry = report
+        .entries()
+        .iter()
+        .find(|entry| entry.id() == id)
+        .unwrap();
+    assert_eq!(entry.health(), Health::Healthy);
+    assert!(entry.reasons().is_empty());
+    patch_gist(&id, r#"{"files": {"_5": {"content": "orphan"}}}"#).await;
+    let report = stash.check().await.unwrap();
+    let entry = report
+        .entries()
+        .iter()
+        .find(|entry| entry.id() == id)
+        .unwrap();
+    assert_eq!(entry.health(), Health::Corrupt);
+    assert!(entry
+        .reasons()
+        .iter()
+        .any(|reason| reason == "chunk `_5` is orphaned"));
+    assert!(entry.repaired().is_empty());
+    let report = stash.check_and_repair().await.unwrap();
+    let entry = report
+        .entries()
+        .iter()
+        .find(|entry| entry.id() == id)
+        .unwrap();
+    assert_eq!(entry.repaired(), ["_5"]);
+    assert!(!stash.gist_files(&id).await.unwrap().contains_key("_5"));
+    assert_eq!(stash.get(&id).await.unwrap(), "Hello, octostash!");
+    stash.remove(&id).await.unwrap();
+}
+
+#[tokio::test]
+async fn sync() {
+    use octostash::stash::SyncAction;
+
+    let stash = stash();
+    let dir = env::temp_dir().join(format!("octostash-sync-{}", std::process::id()));
+    let mirror = env::temp_dir().join(format!("octostash-sync-mirror-{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("nested")).unwrap();
+    std::fs::create_dir_all(&mirror).unwrap();
+    let report = stash.sync(&mirror, None).await.unwrap();
+    assert!(report.namespace().is_none());
+    assert!(report.changes().is_empty());
+    assert!(std::fs::read_dir(&mirror).unwrap().next().is_none());
+    std::fs::write(dir.join("a.toml"), "a = 1").unwrap();
+    std::fs::write(dir.join("nested").join("b.toml"), "b = 2").unwrap();
+    let report = stash.sync_dry_run(&dir, None).await.unwrap();
+    assert_eq!(report.count(SyncAction::Upload), 2);
+    assert!(report.namespace().is_none());
+    #[cfg(unix)]
+    std::os::unix::fs::symlink(dir.join("a.toml"), dir.join("link.toml")).unwrap();
+    let report = stash.sync(&dir, None).await.unwrap();
+    assert_eq!(report.count(SyncAction::Upload), 2);
+    #[cfg(unix)]
+    {
+        assert!(report.failed().iter().any(|(key, _)| key == "link.toml"));
+        std::fs::remove_file(dir.join("link.toml")).unwrap();
+    }
+    let namespace = report.namespace().unwrap().to_owned();
+    assert!(stash.sync(&dir, None).await.unwrap().changes().is_empty());
+    let report = stash.sync(&mirror, Some(&namespace)).await.unwrap();
+    assert_eq!(report.count(SyncAction::Download), 2);
+    assert_eq!(
+        std::fs::read_to_string(mirror.join("nested").join("b.toml")).unwrap(),
+        "b = 2"
+    );
+    std::fs::write(dir.join("a.toml"), "a = 3").unwrap();
+    std::fs::write(mirror.join("a.toml"), "a = 4").unwrap();
+    assert_eq!(
+        stash
+            .sync(&dir, None)
+            .await
+            .unwrap()
+            .count(SyncAction::Upload),
+        1
+    );
+    let report = stash.sync(&mirror, None).await.unwrap();
+    assert_eq!(report.count(SyncAction::Conflict), 1);
+    assert_eq!(
+        std::fs::read_to_string(mirror.join("a.toml")).unwrap(),
+        "a = 4"
+    );
+    std::fs::remove_file(dir.join("nested").join("b.toml")).unwrap();
+    assert_eq!(
+        stash
+            .sync(&dir, None)
+            .await
+            .unwrap()
+            .count(SyncAction::DeleteRemote),
+        1
+    );
+    std::fs::remove_file(dir.join("a.toml")).unwrap();
+    stash.sync(&dir, None).await.unwrap();
+    stash.remove(&namespace).await.unwrap();
+    std::fs::remove_dir_all(&dir).ok();
+    std::fs::remove_dir_all(&mirror).ok();
+}
+
+#[cfg(feature = "server")]
+#[tokio::test]
+async fn server() {
+    use hyper::{Body, Method, Request, StatusCode};
+
+    let addr =
+        std::net::SocketAddr::from(([127, 0, 0, 1], 20000 + (std::process::id() % 10000) as u16));
+    let public = std::net::SocketAddr::from(([0, 0, 0, 0], addr.port()));
+    assert!(octostash::server::serve(stash(), public, None)
+        .await
+        .unwrap_err()
+        .is_invalid());
+    let server =
+        tokio::spawn(async move { octostash::server::serve(stash(), addr, Some("secret")).await });
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    let client = hyper::Client::new();
+    let request = |method: Method, path: &str, body: &'static str| {
+        Request::builder()
+            .method(method)
+            .uri(format!("http://{addr}{path}"))
+            .header(hyper::header::AUTHORIZATION, "Bearer secret")
+            .body(Body::from(body))
+            .unwrap()
+    };
+    for authorization in [None, Some("Bearer wrong"), Some("Basic secret")] {
+        let mut unauthorized = Request::builder().uri(format!("http://{addr}/kv"));
+        if let Some(authorization) = authorization {
+            unauthorized = unauthorized.header(hyper::header::AUTHORIZATION, authorization);
+        }
+        let resp = client
+            .request(unauthorized.body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    }
+    let resp = client
+        .request(request(Method::POST, "/kv", "Hello, octostash!"))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let id = String::from_utf8(hyper::body::to_bytes(resp).await.unwrap().to_vec()).unwrap();
+    let path = format!("/kv/{id}");
+    let resp = client
+        .request(request(Method::PUT, &path, "Hello again, octostash!"))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+    let resp = client
+        .request(request(Method::GET, &path, ""))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(
+        hyper::body::to_bytes(resp).await.unwrap(),
+        "Hello again, octostash!"
+    );
+    let resp = client
+        .request(request(Method::POST, &path, "Hello once more, octostash!"))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+    let resp = client
+        .request(request(Method::GET, &path, ""))
+        .await
+        .unwrap();
+    assert_eq!(
+        hyper::body::to_bytes(resp).await.unwrap(),
+        "Hello once more, octostash!"
+    );
+    let resp = client
+        .request(request(Method::DELETE, &path, ""))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+    let resp = client
+        .request(request(Method::GET, &path, ""))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    let resp = client
+        .request(request(Method::GET, "/kv", ""))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let ids: Vec<String> =
+        serde_json::from_slice(&hyper::body::to_bytes(resp).await.unwrap()).unwrap();
+    assert!(!ids.contains(&id));
+    let oversized = Request::builder()
+        .method(Method::POST)
+        .uri(format!("http://{addr}/kv"))
+        .header(hyper::header::AUTHORIZATION, "Bearer secret")
+        .body(Body::from(vec![b'a'; 32 * 1024 * 1024 + 1]))
+        .unwrap();
+    let resp = client.request(oversized).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    server.abort();
+}